@@ -0,0 +1,419 @@
+//! The ALU from day 24: four integer registers, an input queue and six
+//! instructions.
+//!
+//! Programs are written one instruction per line, as in `add x 1`. Text from
+//! a `#` to the end of the line is a comment, and blank lines are ignored.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::str::FromStr;
+
+pub mod compile;
+pub mod symbolic;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reg {
+    X = 0,
+    Y = 1,
+    Z = 2,
+    W = 3,
+}
+
+impl FromStr for Reg {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "x" => Ok(Reg::X),
+            "y" => Ok(Reg::Y),
+            "z" => Ok(Reg::Z),
+            "w" => Ok(Reg::W),
+            _ => Err(format!("invalid register '{}'", s)),
+        }
+    }
+}
+
+impl fmt::Display for Reg {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Reg::X => "x",
+            Reg::Y => "y",
+            Reg::Z => "z",
+            Reg::W => "w",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegOrIm {
+    Reg(Reg),
+    Im(i64),
+}
+
+impl FromStr for RegOrIm {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.starts_with(|c: char| c.is_alphabetic()) {
+            Ok(RegOrIm::Reg(Reg::from_str(s)?))
+        } else {
+            let im = s
+                .parse()
+                .map_err(|_| format!("invalid immediate '{}'", s))?;
+            Ok(RegOrIm::Im(im))
+        }
+    }
+}
+
+impl fmt::Display for RegOrIm {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RegOrIm::Reg(r) => write!(f, "{}", r),
+            RegOrIm::Im(v) => write!(f, "{}", v),
+        }
+    }
+}
+
+/// The arithmetic done by every instruction but `inp`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinOp {
+    Add,
+    Mul,
+    Div,
+    Mod,
+    Eql,
+}
+
+impl BinOp {
+    /// Division by zero, `mod` with a negative dividend or non-positive
+    /// divisor and overflow are all errors.
+    pub fn apply(self, a: i64, b: i64) -> Result<i64, String> {
+        let overflow = || format!("{:?} of {} and {} overflows", self, a, b);
+        match self {
+            BinOp::Add => a.checked_add(b).ok_or_else(overflow),
+            BinOp::Mul => a.checked_mul(b).ok_or_else(overflow),
+            BinOp::Div => {
+                if b == 0 {
+                    return Err("division by zero".to_string());
+                }
+                a.checked_div(b).ok_or_else(overflow)
+            }
+            BinOp::Mod => {
+                if a < 0 || b <= 0 {
+                    return Err(format!("mod of {} by {}", a, b));
+                }
+                Ok(a % b)
+            }
+            BinOp::Eql => Ok((a == b) as i64),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Inst {
+    Inp(Reg),
+    Add(Reg, RegOrIm),
+    Mul(Reg, RegOrIm),
+    Div(Reg, RegOrIm),
+    Mod(Reg, RegOrIm),
+    Eql(Reg, RegOrIm),
+}
+
+/// Parses one instruction such as `add x 1`. Operands may be separated by
+/// any whitespace.
+impl FromStr for Inst {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split_whitespace();
+        let name = parts.next().ok_or("missing instruction")?;
+        let reg = parts
+            .next()
+            .ok_or(format!("'{}' is missing its register", name))?;
+        let reg = Reg::from_str(reg)?;
+        let inst = if name == "inp" {
+            Inst::Inp(reg)
+        } else {
+            let v = parts
+                .next()
+                .ok_or(format!("'{}' is missing its second operand", name))?;
+            let v = RegOrIm::from_str(v)?;
+            match name {
+                "add" => Inst::Add(reg, v),
+                "mul" => Inst::Mul(reg, v),
+                "div" => Inst::Div(reg, v),
+                "mod" => Inst::Mod(reg, v),
+                "eql" => Inst::Eql(reg, v),
+                _ => return Err(format!("invalid instruction '{}'", name)),
+            }
+        };
+        match parts.next() {
+            Some(extra) => Err(format!("unexpected operand '{}'", extra)),
+            None => Ok(inst),
+        }
+    }
+}
+
+impl fmt::Display for Inst {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Inst::Inp(_) => "inp",
+            Inst::Add(..) => "add",
+            Inst::Mul(..) => "mul",
+            Inst::Div(..) => "div",
+            Inst::Mod(..) => "mod",
+            Inst::Eql(..) => "eql",
+        };
+        write!(f, "{} {}", name, self.dest())?;
+        if let Some(v) = self.source() {
+            write!(f, " {}", v)?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses a whole program, skipping comments and blank lines. Errors carry
+/// the line number and the offending line.
+pub fn parse_program(s: &str) -> Result<Vec<Inst>, String> {
+    let mut insts = vec![];
+    for (i, line) in s.lines().enumerate() {
+        let code = line.split('#').next().unwrap_or("").trim();
+        if code.is_empty() {
+            continue;
+        }
+        let inst = code
+            .parse()
+            .map_err(|e| format!("line {}: {} in: {}", i + 1, e, line.trim()))?;
+        insts.push(inst);
+    }
+    Ok(insts)
+}
+
+/// Lists the program with a comment heading each block, giving the input the
+/// block reads and its instruction numbers. The listing parses back to the
+/// same program.
+pub fn disassemble(insts: &[Inst]) -> String {
+    let mut lines = vec![];
+    let mut start = 0;
+    let mut input = 0;
+    for (i, block) in blocks(insts).iter().enumerate() {
+        let end = start + block.len();
+        let reads = match block.first() {
+            Some(Inst::Inp(_)) => {
+                input += 1;
+                format!("input {}", input - 1)
+            }
+            _ => "no input".to_string(),
+        };
+        lines.push(format!(
+            "# block {}: {}, instructions {}-{}",
+            i,
+            reads,
+            start + 1,
+            end
+        ));
+        lines.extend(block.iter().map(Inst::to_string));
+        start = end;
+    }
+    lines.join("\n")
+}
+
+impl Inst {
+    pub fn dest(&self) -> Reg {
+        match *self {
+            Inst::Inp(r)
+            | Inst::Add(r, _)
+            | Inst::Mul(r, _)
+            | Inst::Div(r, _)
+            | Inst::Mod(r, _)
+            | Inst::Eql(r, _) => r,
+        }
+    }
+
+    pub fn bin_op(&self) -> Option<BinOp> {
+        match self {
+            Inst::Inp(_) => None,
+            Inst::Add(..) => Some(BinOp::Add),
+            Inst::Mul(..) => Some(BinOp::Mul),
+            Inst::Div(..) => Some(BinOp::Div),
+            Inst::Mod(..) => Some(BinOp::Mod),
+            Inst::Eql(..) => Some(BinOp::Eql),
+        }
+    }
+
+    pub fn source(&self) -> Option<RegOrIm> {
+        match *self {
+            Inst::Inp(_) => None,
+            Inst::Add(_, v)
+            | Inst::Mul(_, v)
+            | Inst::Div(_, v)
+            | Inst::Mod(_, v)
+            | Inst::Eql(_, v) => Some(v),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Alu {
+    pub registers: [i64; 4],
+    pub input: VecDeque<i64>,
+}
+
+impl Alu {
+    pub fn new(input: &[i64]) -> Self {
+        Alu {
+            registers: [0; 4],
+            input: input.iter().copied().collect(),
+        }
+    }
+
+    pub fn run_program(&mut self, insts: &[Inst]) -> Result<(), String> {
+        for (i, inst) in insts.iter().enumerate() {
+            self.run_inst(inst)
+                .map_err(|e| format!("instruction {}: {}", i + 1, e))?;
+        }
+        Ok(())
+    }
+
+    /// Runs one instruction, failing as `BinOp::apply` does or when reading
+    /// past the end of the input.
+    pub fn run_inst(&mut self, inst: &Inst) -> Result<(), String> {
+        let r = inst.dest();
+        let a = self.get_reg(r);
+        let b = match inst.source() {
+            Some(RegOrIm::Reg(s)) => self.get_reg(s),
+            Some(RegOrIm::Im(v)) => v,
+            None => 0,
+        };
+        let value = match inst.bin_op() {
+            Some(op) => op.apply(a, b)?,
+            None => self.input.pop_front().ok_or("out of input")?,
+        };
+        self.set_reg(r, value);
+        Ok(())
+    }
+
+    pub fn get_reg(&self, r: Reg) -> i64 {
+        self.registers[r as usize]
+    }
+
+    pub fn set_reg(&mut self, r: Reg, v: i64) {
+        self.registers[r as usize] = v;
+    }
+}
+
+/// The program cut before every `inp`, so each block after the first reads
+/// one digit.
+pub fn blocks(insts: &[Inst]) -> Vec<&[Inst]> {
+    let mut blocks = vec![];
+    let mut start = 0;
+    for (i, inst) in insts.iter().enumerate() {
+        if let Inst::Inp(_) = inst {
+            if i > start {
+                blocks.push(&insts[start..i]);
+            }
+            start = i;
+        }
+    }
+    if start < insts.len() {
+        blocks.push(&insts[start..]);
+    }
+    blocks
+}
+
+/// Registers a block reads before overwriting them. `mul r 0` doesn't count
+/// as a read since the old value can't matter.
+pub fn live_in(block: &[Inst]) -> [bool; 4] {
+    let mut live = [false; 4];
+    let mut written = [false; 4];
+    for inst in block {
+        if let Some(RegOrIm::Reg(s)) = inst.source() {
+            if !written[s as usize] {
+                live[s as usize] = true;
+            }
+        }
+        let r = inst.dest() as usize;
+        let clears = matches!(inst, Inst::Mul(_, RegOrIm::Im(0)));
+        if !written[r] && !matches!(inst, Inst::Inp(_)) && !clears {
+            live[r] = true;
+        }
+        written[r] = true;
+    }
+    live
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn parse(program: &str) -> Vec<Inst> {
+        parse_program(program).unwrap()
+    }
+
+    #[test]
+    fn test_alu() {
+        let binary = parse(
+            "inp w\nadd z w\nmod z 2\ndiv w 2\nadd y w\nmod y 2\ndiv w 2\n\
+             add x w\nmod x 2\ndiv w 2\nmod w 2",
+        );
+        let mut alu = Alu::new(&[13]);
+        alu.run_program(&binary).unwrap();
+        assert_eq!(alu.registers, [1, 0, 1, 1]);
+
+        let negate = parse("inp x\nmul x -1");
+        let mut alu = Alu::new(&[7]);
+        alu.run_program(&negate).unwrap();
+        assert_eq!(alu.get_reg(Reg::X), -7);
+        assert!(Alu::new(&[]).run_program(&negate).is_err());
+
+        assert!(Alu::new(&[]).run_program(&parse("div x 0")).is_err());
+        assert!(Alu::new(&[-3])
+            .run_program(&parse("inp x\nmod x 2"))
+            .is_err());
+        assert!(Alu::new(&[3])
+            .run_program(&parse("inp x\nmod x -2"))
+            .is_err());
+        let mut alu = Alu::new(&[-7]);
+        alu.run_program(&parse("inp x\ndiv x 2")).unwrap();
+        assert_eq!(alu.get_reg(Reg::X), -3);
+    }
+
+    #[test]
+    fn test_text() {
+        let source = "# negate\ninp x\n\nmul x -1  # flip\nmul y 0\ninp w\neql w x\n";
+        let program = parse(source);
+        assert_eq!(program.len(), 5);
+        assert_eq!(program[1], Inst::Mul(Reg::X, RegOrIm::Im(-1)));
+        assert_eq!(program[4].to_string(), "eql w x");
+
+        let listing = disassemble(&program);
+        assert_eq!(
+            listing,
+            "# block 0: input 0, instructions 1-3\ninp x\nmul x -1\nmul y 0\n\
+             # block 1: input 1, instructions 4-5\ninp w\neql w x"
+        );
+        assert_eq!(parse(&listing), program);
+        assert!(disassemble(&parse("add z 1\ninp w")).starts_with("# block 0: no input"));
+
+        let error = |source: &str| parse_program(source).unwrap_err();
+        assert_eq!(
+            error("inp x\n# ok\nadd x"),
+            "line 3: 'add' is missing its second operand in: add x"
+        );
+        assert_eq!(
+            error("inp"),
+            "line 1: 'inp' is missing its register in: inp"
+        );
+        assert_eq!(
+            error("sub x 1"),
+            "line 1: invalid instruction 'sub' in: sub x 1"
+        );
+        assert_eq!(error("add q 1"), "line 1: invalid register 'q' in: add q 1");
+        assert_eq!(
+            error("add x 1.5"),
+            "line 1: invalid immediate '1.5' in: add x 1.5"
+        );
+        assert_eq!(
+            error("inp x y"),
+            "line 1: unexpected operand 'y' in: inp x y"
+        );
+    }
+}
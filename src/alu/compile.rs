@@ -0,0 +1,302 @@
+//! A small compiler for ALU programs. Instructions are lowered to `Op`s,
+//! which add a plain move, optimised, and then either threaded into closures
+//! or printed as a Rust function.
+//!
+//! Optimised programs agree with the interpreter whenever the interpreter
+//! succeeds. A program that fails, say by dividing by zero, may fail
+//! differently or not at all once its dead code is gone.
+
+use super::{BinOp, Inst, Reg, RegOrIm};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    Inp(Reg),
+    Set(Reg, RegOrIm),
+    Bin(BinOp, Reg, RegOrIm),
+}
+
+impl From<&Inst> for Op {
+    fn from(inst: &Inst) -> Self {
+        match (inst.bin_op(), inst.source()) {
+            (Some(op), Some(v)) => Op::Bin(op, inst.dest(), v),
+            _ => Op::Inp(inst.dest()),
+        }
+    }
+}
+
+impl Op {
+    fn dest(&self) -> Reg {
+        match *self {
+            Op::Inp(r) | Op::Set(r, _) | Op::Bin(_, r, _) => r,
+        }
+    }
+
+    fn source(&self) -> Option<RegOrIm> {
+        match *self {
+            Op::Inp(_) => None,
+            Op::Set(_, v) | Op::Bin(_, _, v) => Some(v),
+        }
+    }
+}
+
+// folds constants through the program, starting from the registers in
+// `known`. Once `mul x 0` has made x a known zero, `add x y` becomes a move.
+fn propagate(ops: &[Op], mut known: [Option<i64>; 4]) -> Vec<Op> {
+    let mut result = vec![];
+    for &op in ops {
+        let value = |v: RegOrIm| match v {
+            RegOrIm::Im(c) => Some(c),
+            RegOrIm::Reg(s) => known[s as usize],
+        };
+        let v = op.source().map(|v| match value(v) {
+            Some(c) => RegOrIm::Im(c),
+            None => v,
+        });
+        let r = op.dest();
+        let op = match (op, v) {
+            (Op::Set(_, _), Some(v)) => Some(Op::Set(r, v)),
+            (Op::Bin(bin, _, _), Some(v)) => {
+                let a = known[r as usize];
+                let b = value(v);
+                match (bin, a, b) {
+                    (_, Some(a), Some(b)) => match bin.apply(a, b) {
+                        Ok(c) => Some(Op::Set(r, RegOrIm::Im(c))),
+                        Err(_) => Some(Op::Bin(bin, r, v)),
+                    },
+                    (BinOp::Add, _, Some(0)) | (BinOp::Mul | BinOp::Div, _, Some(1)) => None,
+                    (BinOp::Mul, _, Some(0)) | (BinOp::Mul, Some(0), _) => {
+                        Some(Op::Set(r, RegOrIm::Im(0)))
+                    }
+                    (BinOp::Add, Some(0), _) => Some(Op::Set(r, v)),
+                    (BinOp::Eql, _, _) if v == RegOrIm::Reg(r) => Some(Op::Set(r, RegOrIm::Im(1))),
+                    _ => Some(Op::Bin(bin, r, v)),
+                }
+            }
+            _ => Some(op),
+        };
+        let op = match op {
+            Some(Op::Set(r, RegOrIm::Reg(s))) if r == s => None,
+            op => op,
+        };
+        if let Some(op) = op {
+            known[r as usize] = match op {
+                Op::Set(_, v) => value(v),
+                _ => None,
+            };
+            result.push(op);
+        }
+    }
+    result
+}
+
+// drops every write that nothing reads before it's overwritten or the
+// program ends with the register not in `live_out`. Inputs are always kept
+// as they still consume a value.
+fn eliminate_dead_stores(ops: &[Op], live_out: [bool; 4]) -> Vec<Op> {
+    let mut live = live_out;
+    let mut kept = vec![];
+    for &op in ops.iter().rev() {
+        let r = op.dest() as usize;
+        if !live[r] && !matches!(op, Op::Inp(_)) {
+            continue;
+        }
+        live[r] = matches!(op, Op::Bin(..));
+        if let Some(RegOrIm::Reg(s)) = op.source() {
+            live[s as usize] = true;
+        }
+        kept.push(op);
+    }
+    kept.reverse();
+    kept
+}
+
+/// Lowers and optimises a program whose result is the registers in
+/// `live_out`. `start` holds whichever registers are known up front, which
+/// for a whole program is all of them at zero.
+pub fn optimize(insts: &[Inst], start: [Option<i64>; 4], live_out: [bool; 4]) -> Vec<Op> {
+    let mut ops: Vec<Op> = insts.iter().map(Op::from).collect();
+    loop {
+        let next = eliminate_dead_stores(&propagate(&ops, start), live_out);
+        if next == ops {
+            return ops;
+        }
+        ops = next;
+    }
+}
+
+struct Machine<'a> {
+    registers: [i64; 4],
+    input: &'a [i64],
+}
+
+type Step = Box<dyn Fn(&mut Machine) -> Result<(), String>>;
+
+/// A program threaded into one closure per op.
+pub struct Compiled {
+    steps: Vec<Step>,
+}
+
+impl Compiled {
+    pub fn new(ops: &[Op]) -> Self {
+        let steps = ops
+            .iter()
+            .map(|&op| -> Step {
+                match op {
+                    Op::Inp(r) => Box::new(move |m| {
+                        let (&value, rest) = m.input.split_first().ok_or("out of input")?;
+                        m.registers[r as usize] = value;
+                        m.input = rest;
+                        Ok(())
+                    }),
+                    Op::Set(r, RegOrIm::Im(v)) => Box::new(move |m| {
+                        m.registers[r as usize] = v;
+                        Ok(())
+                    }),
+                    Op::Set(r, RegOrIm::Reg(s)) => Box::new(move |m| {
+                        m.registers[r as usize] = m.registers[s as usize];
+                        Ok(())
+                    }),
+                    Op::Bin(bin, r, RegOrIm::Im(v)) => Box::new(move |m| {
+                        m.registers[r as usize] = bin.apply(m.registers[r as usize], v)?;
+                        Ok(())
+                    }),
+                    Op::Bin(bin, r, RegOrIm::Reg(s)) => Box::new(move |m| {
+                        let v = m.registers[s as usize];
+                        m.registers[r as usize] = bin.apply(m.registers[r as usize], v)?;
+                        Ok(())
+                    }),
+                }
+            })
+            .collect();
+        Compiled { steps }
+    }
+
+    /// Runs from the given registers, returning them afterwards.
+    pub fn run(&self, registers: [i64; 4], input: &[i64]) -> Result<[i64; 4], String> {
+        let mut machine = Machine { registers, input };
+        for step in &self.steps {
+            step(&mut machine)?;
+        }
+        Ok(machine.registers)
+    }
+}
+
+/// Prints the ops as a Rust function from the input digits to the final
+/// registers, panicking where the ALU would fail.
+pub fn to_rust(ops: &[Op], function: &str) -> String {
+    let mut lines = vec![
+        format!("fn {}(input: &[i64]) -> [i64; 4] {{", function),
+        "    let (mut x, mut y, mut z, mut w) = (0i64, 0i64, 0i64, 0i64);".to_string(),
+    ];
+    let mut inputs = 0;
+    for op in ops {
+        let r = op.dest();
+        let v = op.source().map_or(String::new(), |v| v.to_string());
+        let line = match op {
+            Op::Inp(_) => {
+                inputs += 1;
+                format!("{} = input[{}];", r, inputs - 1)
+            }
+            Op::Set(..) => format!("{} = {};", r, v),
+            Op::Bin(BinOp::Add, ..) => format!("{} += {};", r, v),
+            Op::Bin(BinOp::Mul, ..) => format!("{} *= {};", r, v),
+            Op::Bin(BinOp::Div, ..) => format!("{} /= {};", r, v),
+            Op::Bin(BinOp::Mod, ..) => {
+                format!("assert!({} >= 0 && {} > 0);\n    {} %= {};", r, v, r, v)
+            }
+            Op::Bin(BinOp::Eql, ..) => format!("{} = ({} == {}) as i64;", r, r, v),
+        };
+        lines.push(format!("    {}", line));
+    }
+    lines.push("    [x, y, z, w]".to_string());
+    lines.push("}".to_string());
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::alu::Alu;
+    use crate::rng::Rng;
+
+    fn parse(program: &str) -> Vec<Inst> {
+        crate::alu::parse_program(program).unwrap()
+    }
+
+    #[test]
+    fn test_optimize() {
+        let program =
+            parse("inp w\nmul x 0\nadd x z\nmod x 26\nadd x 12\nmul y 0\nadd y w\nadd z y");
+        let ops = optimize(&program, [Some(0); 4], [false, false, true, false]);
+        assert_eq!(
+            ops,
+            [
+                Op::Inp(Reg::W),
+                Op::Set(Reg::Y, RegOrIm::Reg(Reg::W)),
+                Op::Set(Reg::Z, RegOrIm::Reg(Reg::Y)),
+            ]
+        );
+        let ops = optimize(&program, [None; 4], [false, false, true, false]);
+        assert_eq!(
+            ops,
+            [
+                Op::Inp(Reg::W),
+                Op::Set(Reg::Y, RegOrIm::Reg(Reg::W)),
+                Op::Bin(BinOp::Add, Reg::Z, RegOrIm::Reg(Reg::Y)),
+            ]
+        );
+        let ops = optimize(&program, [Some(0); 4], [false, false, true, false]);
+        assert_eq!(
+            to_rust(&ops, "monad"),
+            "fn monad(input: &[i64]) -> [i64; 4] {\n    \
+             let (mut x, mut y, mut z, mut w) = (0i64, 0i64, 0i64, 0i64);\n    \
+             w = input[0];\n    y = w;\n    z = y;\n    [x, y, z, w]\n}"
+        );
+    }
+
+    // random programs over a few small immediates, run on random inputs
+    // through the interpreter and the optimised closures.
+    #[test]
+    fn test_differential() {
+        let mut rng = Rng::new(24);
+        let mut random = |n: u64| rng.below(n);
+        let regs = [Reg::X, Reg::Y, Reg::Z, Reg::W];
+        let mut agreed = 0;
+        for _ in 0..500 {
+            let mut program = vec![];
+            for _ in 0..random(30) {
+                let r = regs[random(4) as usize];
+                let v = if random(2) == 0 {
+                    RegOrIm::Reg(regs[random(4) as usize])
+                } else {
+                    RegOrIm::Im(random(7) as i64 - 2)
+                };
+                program.push(match random(7) {
+                    0 => Inst::Inp(r),
+                    1 | 2 => Inst::Add(r, v),
+                    3 => Inst::Mul(r, v),
+                    4 => Inst::Div(r, v),
+                    5 => Inst::Mod(r, v),
+                    _ => Inst::Eql(r, v),
+                });
+            }
+            let live_out = [random(2) == 0, random(2) == 0, true, random(2) == 0];
+            let compiled = Compiled::new(&optimize(&program, [Some(0); 4], live_out));
+            for _ in 0..10 {
+                let input: Vec<i64> = (0..program.len()).map(|_| random(9) as i64 + 1).collect();
+                let mut alu = Alu::new(&input);
+                if alu.run_program(&program).is_err() {
+                    continue;
+                }
+                let registers = compiled.run([0; 4], &input).unwrap();
+                for r in 0..4 {
+                    if live_out[r] {
+                        assert_eq!(registers[r], alu.registers[r], "{:?}", program);
+                    }
+                }
+                agreed += 1;
+            }
+        }
+        assert!(agreed > 1000);
+    }
+}
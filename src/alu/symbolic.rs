@@ -0,0 +1,455 @@
+//! Symbolic execution of ALU programs over their input digits.
+//!
+//! Registers hold expressions over the inputs `d0`, `d1`, ... (`d0` read
+//! first), each a digit from 1 to 9. Expressions are simplified as they're
+//! built using the range of values they can take, so `eql` of two
+//! expressions with disjoint ranges folds to 0 and `(a * 26 + b) % 26` folds
+//! to `b` when `b` is known to lie in `0..26`. An `eql` that can't be decided
+//! splits execution into a path where its operands are equal and one where
+//! they aren't.
+
+use super::{Inst, Reg, RegOrIm};
+use std::collections::VecDeque;
+use std::fmt;
+use std::rc::Rc;
+
+const DIGITS: (i64, i64) = (1, 9);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expr {
+    Const(i64),
+    Input(usize),
+    Add(Rc<Expr>, Rc<Expr>),
+    Mul(Rc<Expr>, Rc<Expr>),
+    Div(Rc<Expr>, Rc<Expr>),
+    Mod(Rc<Expr>, Rc<Expr>),
+}
+
+fn corners(a: (i64, i64), b: (i64, i64), f: impl Fn(i64, i64) -> Option<i64>) -> (i64, i64) {
+    let values = [f(a.0, b.0), f(a.0, b.1), f(a.1, b.0), f(a.1, b.1)];
+    if values.iter().any(Option::is_none) {
+        return (i64::MIN, i64::MAX);
+    }
+    let values = values.map(Option::unwrap);
+    (*values.iter().min().unwrap(), *values.iter().max().unwrap())
+}
+
+impl Expr {
+    /// The smallest and largest values the expression can take.
+    pub fn range(&self) -> (i64, i64) {
+        match self {
+            Expr::Const(c) => (*c, *c),
+            Expr::Input(_) => DIGITS,
+            Expr::Add(a, b) => {
+                let (a, b) = (a.range(), b.range());
+                (a.0.saturating_add(b.0), a.1.saturating_add(b.1))
+            }
+            Expr::Mul(a, b) => corners(a.range(), b.range(), i64::checked_mul),
+            Expr::Div(a, b) => {
+                let b = b.range();
+                if b.0 <= 0 && b.1 >= 0 {
+                    (i64::MIN, i64::MAX)
+                } else {
+                    corners(a.range(), b, i64::checked_div)
+                }
+            }
+            Expr::Mod(_, b) => (0, b.range().1.saturating_sub(1).max(0)),
+        }
+    }
+
+    fn constant(&self) -> Option<i64> {
+        match self {
+            Expr::Const(c) => Some(*c),
+            _ => None,
+        }
+    }
+
+    fn within(&self, low: i64, high: i64) -> bool {
+        let (min, max) = self.range();
+        min >= low && max <= high
+    }
+
+    // an input plus a constant, as `(input, offset)`.
+    fn linear(&self) -> Option<(usize, i64)> {
+        match self {
+            Expr::Input(i) => Some((*i, 0)),
+            Expr::Add(a, b) => match (a.as_ref(), b.constant()) {
+                (Expr::Input(i), Some(c)) => Some((*i, c)),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    // `(x, y)` if this is `x * k + y` or `x * k` with `x` non-negative and
+    // `y` in `0..k`, so that dividing by `k` gives `x` and the remainder `y`.
+    fn split_by(&self, k: i64) -> Option<(Rc<Expr>, Rc<Expr>)> {
+        let scaled = |e: &Expr| match e {
+            Expr::Mul(x, c) if c.constant() == Some(k) && x.range().0 >= 0 => Some(x.clone()),
+            _ => None,
+        };
+        match self {
+            Expr::Add(a, b) if b.within(0, k - 1) => Some((scaled(a)?, b.clone())),
+            _ => Some((scaled(self)?, Rc::new(Expr::Const(0)))),
+        }
+    }
+}
+
+fn add(a: Rc<Expr>, b: Rc<Expr>) -> Rc<Expr> {
+    match (a.constant(), b.constant()) {
+        (Some(x), Some(y)) if x.checked_add(y).is_some() => Rc::new(Expr::Const(x + y)),
+        (Some(_), None) => add(b, a),
+        (_, Some(0)) => a,
+        // (x + y) + k becomes x + (y + k), gathering constants on the right.
+        (_, Some(_)) => match a.as_ref() {
+            Expr::Add(x, y) => add(x.clone(), add(y.clone(), b)),
+            _ => Rc::new(Expr::Add(a, b)),
+        },
+        _ => Rc::new(Expr::Add(a, b)),
+    }
+}
+
+fn mul(a: Rc<Expr>, b: Rc<Expr>) -> Rc<Expr> {
+    match (a.constant(), b.constant()) {
+        (Some(x), Some(y)) if x.checked_mul(y).is_some() => Rc::new(Expr::Const(x * y)),
+        (Some(_), None) => mul(b, a),
+        (_, Some(0)) => b,
+        (_, Some(1)) => a,
+        _ => Rc::new(Expr::Mul(a, b)),
+    }
+}
+
+fn div(a: Rc<Expr>, b: Rc<Expr>) -> Result<Rc<Expr>, String> {
+    match (a.constant(), b.constant()) {
+        (_, Some(0)) => Err("division by zero".to_string()),
+        (_, Some(1)) => Ok(a),
+        (Some(x), Some(y)) if x.checked_div(y).is_some() => Ok(Rc::new(Expr::Const(x / y))),
+        (_, Some(k)) if k > 0 && a.within(0, k - 1) => Ok(Rc::new(Expr::Const(0))),
+        (_, Some(k)) if k > 0 => match a.split_by(k) {
+            Some((x, _)) => Ok(x),
+            None => Ok(Rc::new(Expr::Div(a, b))),
+        },
+        _ => Ok(Rc::new(Expr::Div(a, b))),
+    }
+}
+
+fn modulo(a: Rc<Expr>, b: Rc<Expr>) -> Result<Rc<Expr>, String> {
+    if a.range().1 < 0 || b.range().1 <= 0 {
+        return Err(format!("mod of {} by {}", a, b));
+    }
+    match (a.constant(), b.constant()) {
+        (Some(x), Some(y)) => Ok(Rc::new(Expr::Const(x % y))),
+        (_, Some(k)) if a.within(0, k - 1) => Ok(a),
+        (_, Some(k)) => match a.split_by(k) {
+            Some((_, y)) => Ok(y),
+            None => Ok(Rc::new(Expr::Mod(a, b))),
+        },
+        _ => Ok(Rc::new(Expr::Mod(a, b))),
+    }
+}
+
+// whether the two are always equal, if that can be told without knowing the inputs.
+fn eql(a: &Expr, b: &Expr) -> Option<bool> {
+    let (a_range, b_range) = (a.range(), b.range());
+    if a == b {
+        Some(true)
+    } else if a_range.1 < b_range.0 || b_range.1 < a_range.0 {
+        Some(false)
+    } else if a_range.0 == a_range.1 && a_range == b_range {
+        Some(true)
+    } else {
+        None
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let operand = |f: &mut fmt::Formatter, e: &Expr| match e {
+            Expr::Add(..) => write!(f, "({})", e),
+            _ => write!(f, "{}", e),
+        };
+        match self {
+            Expr::Const(c) => write!(f, "{}", c),
+            Expr::Input(i) => write!(f, "d{}", i),
+            Expr::Add(a, b) => match b.constant() {
+                Some(c) if c < 0 => write!(f, "{} - {}", a, -(c as i128)),
+                _ => write!(f, "{} + {}", a, b),
+            },
+            Expr::Mul(a, b) | Expr::Div(a, b) | Expr::Mod(a, b) => {
+                let op = match self {
+                    Expr::Mul(..) => "*",
+                    Expr::Div(..) => "/",
+                    _ => "%",
+                };
+                operand(f, a)?;
+                write!(f, " {} ", op)?;
+                operand(f, b)
+            }
+        }
+    }
+}
+
+/// An assumption made at an `eql` that couldn't be decided.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Constraint {
+    pub left: Rc<Expr>,
+    pub right: Rc<Expr>,
+    pub equal: bool,
+}
+
+/// Relates two inputs as `d3 = d4 + 5` where possible, the earlier input first.
+impl fmt::Display for Constraint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let relation = if self.equal { "=" } else { "!=" };
+        match (self.left.linear(), self.right.linear()) {
+            (Some((mut a, mut a_offset)), Some((mut b, mut b_offset))) => {
+                if b < a {
+                    (a, a_offset, b, b_offset) = (b, b_offset, a, a_offset);
+                }
+                let offset = b_offset - a_offset;
+                write!(f, "d{} {} d{}", a, relation, b)?;
+                match offset {
+                    0 => Ok(()),
+                    _ if offset < 0 => write!(f, " - {}", -offset),
+                    _ => write!(f, " + {}", offset),
+                }
+            }
+            _ => write!(f, "{} {} {}", self.left, relation, self.right),
+        }
+    }
+}
+
+/// The smallest and largest digits for each input.
+pub type Bounds = (Vec<i64>, Vec<i64>);
+
+/// One way through the program: the registers at the end and everything
+/// assumed to get there.
+#[derive(Debug, Clone)]
+pub struct Path {
+    pub registers: [Rc<Expr>; 4],
+    pub constraints: Vec<Constraint>,
+    pub inputs: usize,
+}
+
+impl Path {
+    fn new() -> Self {
+        let zero = Rc::new(Expr::Const(0));
+        Path {
+            registers: [zero.clone(), zero.clone(), zero.clone(), zero],
+            constraints: vec![],
+            inputs: 0,
+        }
+    }
+
+    pub fn get_reg(&self, r: Reg) -> &Rc<Expr> {
+        &self.registers[r as usize]
+    }
+
+    fn with(mut self, r: Reg, value: Rc<Expr>) -> Self {
+        self.registers[r as usize] = value;
+        self
+    }
+
+    fn step(mut self, inst: &Inst) -> Result<Vec<Path>, String> {
+        let r = inst.dest();
+        let a = self.get_reg(r).clone();
+        let b = match inst.source() {
+            Some(RegOrIm::Reg(s)) => self.get_reg(s).clone(),
+            Some(RegOrIm::Im(v)) => Rc::new(Expr::Const(v)),
+            None => Rc::new(Expr::Const(0)),
+        };
+        let value = match inst {
+            Inst::Inp(_) => {
+                self.inputs += 1;
+                Rc::new(Expr::Input(self.inputs - 1))
+            }
+            Inst::Add(..) => add(a, b),
+            Inst::Mul(..) => mul(a, b),
+            Inst::Div(..) => div(a, b)?,
+            Inst::Mod(..) => modulo(a, b)?,
+            Inst::Eql(..) => match eql(&a, &b) {
+                Some(equal) => Rc::new(Expr::Const(equal as i64)),
+                None => {
+                    let mut paths = vec![];
+                    for equal in [true, false] {
+                        let mut path = self.clone();
+                        path.constraints.push(Constraint {
+                            left: a.clone(),
+                            right: b.clone(),
+                            equal,
+                        });
+                        paths.push(path.with(r, Rc::new(Expr::Const(equal as i64))));
+                    }
+                    return Ok(paths);
+                }
+            },
+        };
+        Ok(vec![self.with(r, value)])
+    }
+
+    /// Whether the path ends with `z` at zero: always, never, or depending
+    /// on the inputs in a way the ranges can't tell.
+    pub fn accepts(&self) -> Option<bool> {
+        let z = self.get_reg(Reg::Z);
+        let (low, high) = z.range();
+        if z.constant() == Some(0) {
+            Some(true)
+        } else if low > 0 || high < 0 {
+            Some(false)
+        } else {
+            None
+        }
+    }
+
+    /// The smallest and largest inputs meeting every constraint, as digits,
+    /// or `None` if they contradict each other. Only equalities between two
+    /// inputs, each plus a constant, are understood.
+    pub fn digit_bounds(&self) -> Result<Option<Bounds>, String> {
+        // links[i] holds (j, k) for every d_j = d_i + k.
+        let mut links = vec![vec![]; self.inputs];
+        for constraint in &self.constraints {
+            match (constraint.left.linear(), constraint.right.linear()) {
+                (Some((a, a_offset)), Some((b, b_offset))) if constraint.equal => {
+                    links[a].push((b, a_offset - b_offset));
+                    links[b].push((a, b_offset - a_offset));
+                }
+                _ => return Err(format!("can't solve constraint {}", constraint)),
+            }
+        }
+        let mut offsets: Vec<Option<i64>> = vec![None; self.inputs];
+        let mut smallest = vec![0; self.inputs];
+        let mut largest = vec![0; self.inputs];
+        for root in 0..self.inputs {
+            if offsets[root].is_some() {
+                continue;
+            }
+            // offsets of every input tied to root, relative to it.
+            offsets[root] = Some(0);
+            let mut group = vec![root];
+            let mut queue = VecDeque::from([root]);
+            while let Some(i) = queue.pop_front() {
+                for &(j, k) in &links[i] {
+                    let offset = offsets[i].unwrap() + k;
+                    match offsets[j] {
+                        Some(known) if known != offset => return Ok(None),
+                        Some(_) => (),
+                        None => {
+                            offsets[j] = Some(offset);
+                            group.push(j);
+                            queue.push_back(j);
+                        }
+                    }
+                }
+            }
+            let low = group.iter().map(|&i| offsets[i].unwrap()).min().unwrap();
+            let high = group.iter().map(|&i| offsets[i].unwrap()).max().unwrap();
+            if DIGITS.0 - low > DIGITS.1 - high {
+                return Ok(None);
+            }
+            for &i in &group {
+                smallest[i] = DIGITS.0 - low + offsets[i].unwrap();
+                largest[i] = DIGITS.1 - high + offsets[i].unwrap();
+            }
+        }
+        Ok(Some((smallest, largest)))
+    }
+}
+
+/// Runs the program on symbolic inputs, returning every path through it.
+pub fn execute(insts: &[Inst]) -> Result<Vec<Path>, String> {
+    let mut paths = vec![Path::new()];
+    for (i, inst) in insts.iter().enumerate() {
+        let mut next = vec![];
+        for path in paths {
+            next.extend(
+                path.step(inst)
+                    .map_err(|e| format!("instruction {}: {}", i + 1, e))?,
+            );
+        }
+        paths = next;
+    }
+    Ok(paths)
+}
+
+fn same_shape(a: &Inst, b: &Inst) -> bool {
+    let operand = |inst: &Inst| match inst.source() {
+        Some(RegOrIm::Reg(r)) => Some(r),
+        _ => None,
+    };
+    std::mem::discriminant(a) == std::mem::discriminant(b)
+        && a.dest() == b.dest()
+        && operand(a) == operand(b)
+}
+
+/// Checks that the blocks all have the same instructions apart from their
+/// immediates, returning the positions of the immediates that differ.
+pub fn block_template(blocks: &[&[Inst]]) -> Option<Vec<usize>> {
+    let first = blocks.first()?;
+    if blocks.iter().any(|block| {
+        block.len() != first.len()
+            || !block
+                .iter()
+                .zip(first.iter())
+                .all(|(a, b)| same_shape(a, b))
+    }) {
+        return None;
+    }
+    Some(
+        (0..first.len())
+            .filter(|&i| blocks.iter().any(|block| block[i] != first[i]))
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn parse(program: &str) -> Vec<Inst> {
+        crate::alu::parse_program(program).unwrap()
+    }
+
+    // a MONAD style block, pushing d + add_y onto z in base 26 or popping
+    // and comparing against it depending on div.
+    fn block(div: i64, add_x: i64, add_y: i64) -> String {
+        format!(
+            "inp w\nmul x 0\nadd x z\nmod x 26\ndiv z {}\nadd x {}\neql x w\neql x 0\n\
+             mul y 0\nadd y 25\nmul y x\nadd y 1\nmul z y\nmul y 0\nadd y w\nadd y {}\n\
+             mul y x\nadd z y\n",
+            div, add_x, add_y
+        )
+    }
+
+    #[test]
+    fn test_execute() {
+        let source: String = [(1, 12, 4), (1, 11, 1), (26, -6, 0), (26, -2, 5)]
+            .iter()
+            .map(|&(div, add_x, add_y)| block(div, add_x, add_y))
+            .collect();
+        let program = parse(&source);
+        let blocks = super::super::blocks(&program);
+        assert_eq!(block_template(&blocks), Some(vec![4, 5, 15]));
+
+        let paths = execute(&program).unwrap();
+        assert_eq!(paths.len(), 4);
+        assert!(paths.iter().all(|path| path.accepts().is_some()));
+        let accepting: Vec<&Path> = paths.iter().filter(|p| p.accepts() == Some(true)).collect();
+        assert_eq!(accepting.len(), 1);
+        let constraints: Vec<String> = accepting[0]
+            .constraints
+            .iter()
+            .map(|c| c.to_string())
+            .collect();
+        assert_eq!(constraints, ["d1 = d2 + 5", "d0 = d3 - 2"]);
+        let bounds = accepting[0].digit_bounds().unwrap();
+        assert_eq!(bounds, Some((vec![1, 6, 1, 3], vec![7, 9, 4, 9])));
+
+        // disjoint ranges fold without splitting.
+        let program = parse("inp x\nadd x 10\ninp y\neql x y\nadd z x");
+        let paths = execute(&program).unwrap();
+        assert_eq!(paths.len(), 1);
+        assert_eq!(paths[0].accepts(), Some(true));
+        assert!(execute(&parse("inp x\nmul x -1\nmod x 2")).is_err());
+        assert!(execute(&parse("div x 0")).is_err());
+    }
+}
@@ -0,0 +1,334 @@
+//! Two-state cellular automata over a 3x3 neighbourhood.
+//!
+//! A rule is a 512-entry table indexed by the neighbourhood read row by row,
+//! top-left cell as the most significant bit. Life-like `B3/S23` rules are
+//! expanded into the same table.
+//!
+//! Day 11's octopuses don't fit: they have ten energy levels rather than
+//! two states, and a flash cascades within a step instead of every cell
+//! updating once from the previous generation, so day 11 keeps its own grid.
+
+use crate::export;
+use crate::grid::GridView;
+use std::str::FromStr;
+
+const CENTER_BIT: usize = 1 << 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Boundary {
+    /// Cells outside the grid are always dead.
+    Bounded,
+    /// The grid wraps around at its edges.
+    Toroidal,
+    /// The grid sits on an infinite background that evolves under the rule,
+    /// growing by one cell per side each generation.
+    Infinite,
+}
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct Rule {
+    table: [bool; 512],
+}
+
+impl Rule {
+    pub fn from_table(table: [bool; 512]) -> Self {
+        Self { table }
+    }
+
+    pub fn next(&self, index: usize) -> bool {
+        self.table[index]
+    }
+}
+
+/// Parses a Life-like rule such as `B3/S23`.
+impl FromStr for Rule {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut born = [false; 9];
+        let mut survive = [false; 9];
+        let mut parts = s.trim().split('/');
+        for (prefix, counts) in [('B', &mut born), ('S', &mut survive)] {
+            let part = parts
+                .next()
+                .ok_or(format!("missing {} section in rule: {}", prefix, s))?;
+            let mut chars = part.chars();
+            if chars.next().map(|c| c.to_ascii_uppercase()) != Some(prefix) {
+                return Err(format!("expected {} section in rule: {}", prefix, s));
+            }
+            for c in chars {
+                match c.to_digit(10) {
+                    Some(n) if n <= 8 => counts[n as usize] = true,
+                    _ => return Err(format!("invalid neighbour count '{}' in rule: {}", c, s)),
+                }
+            }
+        }
+        if parts.next().is_some() {
+            return Err(format!("unexpected section in rule: {}", s));
+        }
+
+        let mut table = [false; 512];
+        for (index, next) in table.iter_mut().enumerate() {
+            let neighbors = (index & !CENTER_BIT).count_ones() as usize;
+            *next = if index & CENTER_BIT != 0 {
+                survive[neighbors]
+            } else {
+                born[neighbors]
+            };
+        }
+        Ok(Rule { table })
+    }
+}
+
+/// Live and dead cells packed into `u64` words per row, bit 0 of a row's
+/// first word being its leftmost cell. Everything outside is `background`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BitGrid {
+    width: usize,
+    height: usize,
+    words_per_row: usize,
+    data: Vec<u64>,
+    background: bool,
+}
+
+impl BitGrid {
+    pub fn new(width: usize, height: usize, background: bool) -> Self {
+        let words_per_row = width.div_ceil(64);
+        Self {
+            width,
+            height,
+            words_per_row,
+            data: vec![0; words_per_row * height],
+            background,
+        }
+    }
+
+    /// Parses rows of `#` (alive) and `.` (dead).
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let lines: Vec<&str> = input.lines().collect();
+        let width = lines.first().map(|line| line.chars().count()).unwrap_or(0);
+        let mut grid = BitGrid::new(width, lines.len(), false);
+        for (y, line) in lines.iter().enumerate() {
+            if line.chars().count() != width {
+                return Err(format!("line {} has a different width", y + 1));
+            }
+            for (x, c) in line.chars().enumerate() {
+                match c {
+                    '#' => grid.set((x, y), true),
+                    '.' => (),
+                    _ => return Err(format!("unexpected char '{}' on line {}", c, y + 1)),
+                }
+            }
+        }
+        Ok(grid)
+    }
+
+    pub fn background(&self) -> bool {
+        self.background
+    }
+
+    /// The cell at `pos`, falling back to the background outside the grid.
+    pub fn cell(&self, pos: (isize, isize)) -> bool {
+        let (x, y) = pos;
+        if x < 0 || y < 0 || x as usize >= self.width || y as usize >= self.height {
+            return self.background;
+        }
+        let (x, y) = (x as usize, y as usize);
+        let word = self.data[y * self.words_per_row + x / 64];
+        (word >> (x % 64)) & 1 == 1
+    }
+
+    pub fn set(&mut self, pos: (usize, usize), value: bool) {
+        let (x, y) = pos;
+        let word = &mut self.data[y * self.words_per_row + x / 64];
+        if value {
+            *word |= 1 << (x % 64);
+        } else {
+            *word &= !(1 << (x % 64));
+        }
+    }
+
+    /// Live cells inside the grid, or `None` if the background is alive.
+    pub fn count_alive(&self) -> Option<usize> {
+        if self.background {
+            None
+        } else {
+            Some(
+                self.data
+                    .iter()
+                    .map(|word| word.count_ones() as usize)
+                    .sum(),
+            )
+        }
+    }
+
+    /// Plain PBM, where 1 is a black pixel and so a live cell.
+    pub fn to_pbm(&self) -> String {
+        export::to_pbm(self, |_, alive| alive, None)
+    }
+}
+
+impl GridView for BitGrid {
+    type Item = bool;
+
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    fn height(&self) -> usize {
+        self.height
+    }
+
+    fn get(&self, pos: (isize, isize)) -> Option<bool> {
+        if self.contains(pos) {
+            Some(self.cell(pos))
+        } else {
+            None
+        }
+    }
+}
+
+pub struct Automaton {
+    cells: BitGrid,
+    rule: Rule,
+    boundary: Boundary,
+    generation: usize,
+}
+
+impl Automaton {
+    pub fn new(mut cells: BitGrid, rule: Rule, boundary: Boundary) -> Self {
+        if boundary != Boundary::Infinite {
+            cells.background = false;
+        }
+        Self {
+            cells,
+            rule,
+            boundary,
+            generation: 0,
+        }
+    }
+
+    pub fn cells(&self) -> &BitGrid {
+        &self.cells
+    }
+
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    fn cell(&self, x: isize, y: isize) -> bool {
+        match self.boundary {
+            Boundary::Toroidal => {
+                let x = x.rem_euclid(self.cells.width as isize);
+                let y = y.rem_euclid(self.cells.height as isize);
+                self.cells.cell((x, y))
+            }
+            _ => self.cells.cell((x, y)),
+        }
+    }
+
+    // the three vertically stacked cells of column x centred on row y, as
+    // they appear in each 3-bit group of the rule index.
+    fn column_bits(&self, x: isize, y: isize) -> usize {
+        ((self.cell(x, y - 1) as usize) << 6)
+            | ((self.cell(x, y) as usize) << 3)
+            | self.cell(x, y + 1) as usize
+    }
+
+    /// Advances one generation, sliding the 3x3 index along each row so every
+    /// cell costs one column read rather than nine.
+    pub fn step(&mut self) {
+        let grow = if self.boundary == Boundary::Infinite {
+            1
+        } else {
+            0
+        };
+        let mut next = BitGrid::new(
+            self.cells.width + 2 * grow,
+            self.cells.height + 2 * grow,
+            false,
+        );
+        let offset = grow as isize;
+        for new_y in 0..next.height {
+            let y = new_y as isize - offset;
+            let mut index = (self.column_bits(-offset - 1, y) << 1) | self.column_bits(-offset, y);
+            for new_x in 0..next.width {
+                let x = new_x as isize - offset;
+                index = ((index << 1) & 0b110_110_110) | self.column_bits(x + 1, y);
+                if self.rule.next(index) {
+                    next.set((new_x, new_y), true);
+                }
+            }
+        }
+        if self.boundary == Boundary::Infinite {
+            next.background = self.rule.next(if self.cells.background { 511 } else { 0 });
+        }
+        self.cells = next;
+        self.generation += 1;
+    }
+
+    /// Runs `generations` steps, stopping early once a bounded or toroidal
+    /// grid stops changing.
+    pub fn run(&mut self, generations: usize) {
+        let target = self.generation + generations;
+        while self.generation < target {
+            let previous = self.cells.clone();
+            self.step();
+            if self.boundary != Boundary::Infinite && self.cells == previous {
+                self.generation = target;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn life(input: &str, boundary: Boundary) -> Automaton {
+        let rule: Rule = "B3/S23".parse().unwrap();
+        Automaton::new(BitGrid::parse(input).unwrap(), rule, boundary)
+    }
+
+    #[test]
+    fn test_rule_parsing() {
+        assert!("B3/S23".parse::<Rule>().is_ok());
+        assert!("b36/s23".parse::<Rule>().is_ok());
+        assert!("B9/S23".parse::<Rule>().is_err());
+        assert!("S23/B3".parse::<Rule>().is_err());
+        assert!("B3".parse::<Rule>().is_err());
+    }
+
+    #[test]
+    fn test_bounded_blinker() {
+        let horizontal = ".....\n.....\n.###.\n.....\n.....";
+        let vertical = ".....\n..#..\n..#..\n..#..\n.....";
+        let mut automaton = life(horizontal, Boundary::Bounded);
+        automaton.step();
+        assert_eq!(automaton.cells(), &BitGrid::parse(vertical).unwrap());
+        automaton.run(1001);
+        assert_eq!(automaton.generation(), 1002);
+        assert_eq!(automaton.cells(), &BitGrid::parse(horizontal).unwrap());
+    }
+
+    #[test]
+    fn test_toroidal_glider() {
+        let glider = ".#....\n..#...\n###...\n......\n......\n......";
+        let mut automaton = life(glider, Boundary::Toroidal);
+        automaton.run(4);
+        assert_ne!(automaton.cells(), &BitGrid::parse(glider).unwrap());
+        automaton.run(20);
+        assert_eq!(automaton.cells(), &BitGrid::parse(glider).unwrap());
+    }
+
+    #[test]
+    fn test_infinite_background() {
+        let rule: Rule = "B0/S".parse().unwrap();
+        let mut automaton = Automaton::new(BitGrid::parse("#").unwrap(), rule, Boundary::Infinite);
+        automaton.step();
+        assert_eq!(automaton.cells().width(), 3);
+        assert_eq!(automaton.cells().count_alive(), None);
+        automaton.step();
+        assert_eq!(automaton.cells().height(), 5);
+        assert_eq!(automaton.cells().count_alive(), Some(1));
+    }
+}
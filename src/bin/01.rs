@@ -1,33 +1,29 @@
-use std::fs;
-use std::num::ParseIntError;
+use aoc_2021::helpers::{self, number};
+use aoc_2021::runner::Runner;
 
 fn main() {
-    part1();
-    part2();
+    let runner = Runner::from_args(1);
+    runner.part(1, part1);
+    runner.part(2, part2);
 }
 
 fn get_input() -> Vec<u32> {
-    let input = fs::read_to_string("inputs/01.txt").unwrap();
-    let values: Result<Vec<u32>, ParseIntError> = input.lines().map(str::parse).collect();
-    values.unwrap()
+    let input = helpers::read_input(1);
+    helpers::lines(&input, number).unwrap()
 }
 
-fn part1() {
+fn part1() -> usize {
     let input = get_input();
 
     let iter1 = input.iter();
     let iter2 = input.iter().skip(1);
-    let total_count = iter1.zip(iter2).filter(|(a, b)| a < b).count();
-
-    println!("part1: {}", total_count);
+    iter1.zip(iter2).filter(|(a, b)| a < b).count()
 }
 
-fn part2() {
+fn part2() -> usize {
     let input = get_input();
 
     let iter1 = input.iter();
     let iter2 = input.iter().skip(3);
-    let total_count = iter1.zip(iter2).filter(|(a, b)| a < b).count();
-
-    println!("part2: {}", total_count);
+    iter1.zip(iter2).filter(|(a, b)| a < b).count()
 }
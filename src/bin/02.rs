@@ -1,8 +1,11 @@
-use std::{fs, str::FromStr};
+use aoc_2021::helpers::{self, number, split_pair};
+use aoc_2021::runner::Runner;
+use std::str::FromStr;
 
 fn main() {
-    part1();
-    part2();
+    let runner = Runner::from_args(2);
+    runner.part(1, part1);
+    runner.part(2, part2);
 }
 
 #[derive(Debug)]
@@ -15,12 +18,8 @@ enum Command {
 impl FromStr for Command {
     type Err = String;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let parts: Vec<&str> = s.split_whitespace().collect();
-        if parts.len() != 2 {
-            return Err("Missing parts.".to_string());
-        }
-        let direction = parts[0];
-        let units = parts[1].parse().unwrap();
+        let (direction, units) = split_pair(s, " ")?;
+        let units = number(units)?;
         match direction {
             "forward" => Ok(Self::Forward(units)),
             "down" => Ok(Self::Down(units)),
@@ -31,13 +30,11 @@ impl FromStr for Command {
 }
 
 fn get_input() -> Vec<Command> {
-    let input = fs::read_to_string("inputs/02.txt").unwrap();
-    let input: Result<Vec<Command>, String> =
-        input.lines().map(|line| Command::from_str(line)).collect();
-    input.unwrap()
+    let input = helpers::read_input(2);
+    helpers::lines(&input, Command::from_str).unwrap()
 }
 
-fn part1() {
+fn part1() -> u32 {
     let input = get_input();
 
     let mut h = 0;
@@ -51,10 +48,10 @@ fn part1() {
         }
     }
 
-    println!("part1: {}", h * d);
+    h * d
 }
 
-fn part2() {
+fn part2() -> u32 {
     let input = get_input();
 
     let mut a = 0;
@@ -72,5 +69,5 @@ fn part2() {
         }
     }
 
-    println!("part2: {}", h * d);
+    h * d
 }
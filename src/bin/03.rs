@@ -1,32 +1,32 @@
-use std::fs;
+use aoc_2021::helpers;
+use aoc_2021::runner::Runner;
 use std::iter;
 
 fn main() {
-    part1();
-    part2();
+    let runner = Runner::from_args(3);
+    runner.part(1, part1);
+    runner.part(2, part2);
 }
 
 fn get_input() -> Vec<Vec<u8>> {
-    let input = fs::read_to_string("inputs/03.txt").unwrap();
-    input
-        .lines()
-        .map(|line| {
-            line.chars()
-                .map(|c| match c {
-                    '0' => 0,
-                    '1' => 1,
-                    c => panic!("invalid char: {}", c),
-                })
-                .collect::<Vec<u8>>()
-        })
-        .collect()
+    let input = helpers::read_input(3);
+    helpers::lines(&input, |line| {
+        line.chars()
+            .map(|c| match c {
+                '0' => Ok(0),
+                '1' => Ok(1),
+                c => Err(format!("invalid bit '{}'", c)),
+            })
+            .collect()
+    })
+    .unwrap()
 }
 
 fn bin_to_int(vals: Vec<u8>) -> u32 {
     vals.iter().fold(0, |val, &i| (val << 1) | i as u32)
 }
 
-fn part1() {
+fn part1() -> u32 {
     let input = get_input();
     let char_count = input[0].len();
     let mut acc: Vec<i32> = vec![0; char_count];
@@ -36,10 +36,10 @@ fn part1() {
         }
     }
     let gamma: u32 = bin_to_int(acc.iter().map(|i| if *i > 0 { 1 } else { 0 }).collect());
-    let mask = bin_to_int(iter::repeat(1).take(char_count).collect());
+    let mask = bin_to_int(iter::repeat_n(1, char_count).collect());
     let epsilon: u32 = !gamma & mask;
 
-    println!("part1: {}", gamma * epsilon);
+    gamma * epsilon
 }
 
 enum FilterType {
@@ -53,32 +53,48 @@ fn part2_filter(lines: Vec<Vec<u8>>, filter_type: FilterType, pos: usize) -> Vec
         acc += if line[pos] == 0 { -1 } else { 1 }
     }
     let acc = match filter_type {
-        FilterType::MostCommon => { if acc >= 0 { 1 } else { 0 } }
-        FilterType::LeastCommon => { if acc >= 0 { 0 } else { 1 } }
+        FilterType::MostCommon => {
+            if acc >= 0 {
+                1
+            } else {
+                0
+            }
+        }
+        FilterType::LeastCommon => {
+            if acc >= 0 {
+                0
+            } else {
+                1
+            }
+        }
     };
     lines.into_iter().filter(|line| line[pos] == acc).collect()
 }
 
-fn part2() {
+fn part2() -> u32 {
     let input = get_input();
     let char_count = input[0].len();
 
     let mut generator = input.clone();
     for i in 0..char_count {
         generator = part2_filter(generator, FilterType::MostCommon, i);
-        if generator.len() == 1 { break; };
+        if generator.len() == 1 {
+            break;
+        };
     }
 
     let mut scrubber = input.clone();
     for i in 0..char_count {
         scrubber = part2_filter(scrubber, FilterType::LeastCommon, i);
-        if scrubber.len() == 1 { break; };
+        if scrubber.len() == 1 {
+            break;
+        };
     }
 
     let generator = bin_to_int(generator.pop().unwrap());
     let scrubber = bin_to_int(scrubber.pop().unwrap());
 
-    println!("part1: {}", generator * scrubber);
+    generator * scrubber
 }
 
 #[cfg(test)]
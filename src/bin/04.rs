@@ -1,5 +1,6 @@
+use aoc_2021::helpers::{self, list, number, words};
+use aoc_2021::runner::Runner;
 use std::fmt;
-use std::fs;
 use std::ops::Index;
 
 struct Cell(u32, bool);
@@ -11,20 +12,20 @@ struct Board {
 }
 
 impl Board {
-    fn row_iter<'a>(&'a self, row: usize) -> impl Iterator<Item = &Cell> + 'a {
+    fn row_iter(&self, row: usize) -> impl Iterator<Item = &Cell> + '_ {
         (0..self.width).map(move |x| &self[(x, row)])
     }
 
-    fn col_iter<'a>(&'a self, col: usize) -> impl Iterator<Item = &Cell> + 'a {
+    fn col_iter(&self, col: usize) -> impl Iterator<Item = &Cell> + '_ {
         (0..self.height).map(move |y| &self[(col, y)])
     }
 
     fn check_row(&self, row: usize) -> bool {
-        self.row_iter(row).fold(true, |acc, cell| acc && cell.1)
+        self.row_iter(row).all(|cell| cell.1)
     }
 
     fn check_col(&self, col: usize) -> bool {
-        self.col_iter(col).fold(true, |acc, cell| acc && cell.1)
+        self.col_iter(col).all(|cell| cell.1)
     }
 
     fn unmarked_sum(&self) -> u32 {
@@ -66,7 +67,7 @@ impl Index<(usize, usize)> for Board {
 
 impl fmt::Display for Board {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "\n")?;
+        writeln!(f)?;
         for y in 0..self.height {
             for x in 0..self.width {
                 let cell = &self[(x, y)];
@@ -78,7 +79,7 @@ impl fmt::Display for Board {
                     write!(f, "\x1b[0m")?;
                 }
             }
-            write!(f, "\n")?
+            writeln!(f)?
         }
         Ok(())
     }
@@ -90,48 +91,33 @@ struct Input {
 }
 
 fn get_input() -> Input {
-    let input = fs::read_to_string("inputs/04.txt").unwrap();
-    let mut lines = input.lines();
-
-    let first_line = lines.next().unwrap();
-    let numbers = first_line
-        .split(',')
-        .map(|number| number.parse().unwrap())
-        .collect::<Vec<u32>>();
-
-    let mut boards: Vec<Board> = vec![];
-    lines.next();
-    'outer: loop {
-        let mut items: Vec<Cell> = vec![];
-        loop {
-            let line = lines.next();
-            match line {
-                None => break 'outer,
-                Some("") => break,
-                Some(line) => {
-                    line.split_whitespace()
-                        .map(|number| number.parse().unwrap())
-                        .for_each(|number| items.push(Cell(number, false)));
-                }
+    let input = helpers::read_input(4);
+    let sections = helpers::sections(&input);
+    let (numbers, boards) = sections.split_first().unwrap();
+    let numbers = numbers.parse_line(|line| list(line, ",", number)).unwrap();
+    let boards = boards
+        .iter()
+        .map(|board| {
+            let rows = board.parse_lines(|line| words(line, number)).unwrap();
+            Board {
+                width: 5,
+                height: 5,
+                complete: false,
+                items: rows.concat().into_iter().map(|n| Cell(n, false)).collect(),
             }
-        }
-        boards.push(Board {
-            width: 5,
-            height: 5,
-            complete: false,
-            items: items,
-        });
-    }
+        })
+        .collect();
 
     Input { numbers, boards }
 }
 
 fn main() {
-    part1();
-    part2();
+    let runner = Runner::from_args(4);
+    runner.part(1, part1);
+    runner.part(2, part2);
 }
 
-fn part1() {
+fn part1() -> u32 {
     let input = get_input();
     let numbers = input.numbers;
     let mut boards = input.boards;
@@ -147,13 +133,12 @@ fn part1() {
         }
     }
 
-    if let Some(board) = winning_board {
-        println!("part1: {}", board.unmarked_sum() * last_number);
-    }
+    let board = winning_board.expect("Winning board not found.");
+    board.unmarked_sum() * last_number
 }
 
 // separate function required due to bug in the borrow checker.
-fn find_winning_board<'a>(number: u32, boards: &'a mut Vec<Board>) -> Option<&'a Board> {
+fn find_winning_board(number: u32, boards: &mut [Board]) -> Option<&Board> {
     for board in boards {
         board.mark_number(number);
         if board.complete {
@@ -163,7 +148,7 @@ fn find_winning_board<'a>(number: u32, boards: &'a mut Vec<Board>) -> Option<&'a
     None
 }
 
-fn part2() {
+fn part2() -> u32 {
     let input = get_input();
     let numbers = input.numbers;
     let mut boards = input.boards;
@@ -181,16 +166,12 @@ fn part2() {
             }
             remaining_boards.push(board);
         }
-        if remaining_boards.len() == 0 {
+        if remaining_boards.is_empty() {
             break;
         }
         boards = remaining_boards;
     }
 
-    match last_winning_board {
-        None => panic!("Winning board not found."),
-        Some(board) => {
-            println!("part2: {}", board.unmarked_sum() * last_number);
-        }
-    }
+    let board = last_winning_board.expect("Winning board not found.");
+    board.unmarked_sum() * last_number
 }
@@ -1,5 +1,10 @@
+use aoc_2021::export;
+use aoc_2021::grid::Grid;
+use aoc_2021::helpers::{self, number, pair, rule};
+use aoc_2021::render::ColorMap;
+use aoc_2021::runner::Runner;
 use std::collections::HashMap;
-use std::fs;
+use std::env;
 use std::str::FromStr;
 
 #[derive(Eq, PartialEq, Debug, Clone, Copy, Hash)]
@@ -57,9 +62,7 @@ impl Segment {
 impl FromStr for Point {
     type Err = String;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut parts = s.split(',');
-        let x: i32 = parts.next().unwrap().parse().unwrap();
-        let y: i32 = parts.next().unwrap().parse().unwrap();
+        let (x, y) = pair(s, ",", number)?;
         Ok(Point { x, y })
     }
 }
@@ -67,29 +70,55 @@ impl FromStr for Point {
 impl FromStr for Segment {
     type Err = String;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut parts = s.split(" -> ");
-        let start = Point::from_str(parts.next().unwrap())?;
-        let end = Point::from_str(parts.next().unwrap())?;
+        let (start, end) = rule(s, Point::from_str, Point::from_str)?;
         Ok(Segment { start, end })
     }
 }
 
 fn main() {
-    part1();
-    part2();
+    let runner = Runner::from_args(5);
+    runner.part(1, part1);
+    runner.part(2, part2);
+
+    // cargo run --bin 05 -- --export <path.pbm|ppm|svg> for the vent map.
+    let args: Vec<String> = env::args().skip(1).collect();
+    if let Some(i) = args.iter().position(|arg| arg == "--export") {
+        export_vents(args.get(i + 1).expect("--export needs a path")).unwrap();
+    }
+}
+
+// every vent line drawn over a white background, overlaps in hotter colours.
+fn export_vents(path: &str) -> Result<(), String> {
+    let mut counts: HashMap<Point, i32> = HashMap::new();
+    for segment in get_input() {
+        for point in segment.interpolate_points() {
+            *counts.entry(point).or_insert(0) += 1;
+        }
+    }
+    if counts.keys().any(|point| point.x < 0 || point.y < 0) {
+        return Err("can't export vents at negative coordinates".to_string());
+    }
+    let width = counts.keys().map(|point| point.x + 1).max().unwrap_or(0) as usize;
+    let height = counts.keys().map(|point| point.y + 1).max().unwrap_or(0) as usize;
+    let mut data = vec![0; width * height];
+    for (point, count) in counts {
+        data[point.x as usize + point.y as usize * width] = count;
+    }
+    let max = data.iter().copied().max().unwrap_or(1).max(2);
+    let color = |_, count: i32| match count {
+        0 => export::WHITE,
+        1 => (120, 120, 120),
+        _ => ColorMap::Heat.rgb(0.3 + 0.5 * (count - 2) as f64 / (max - 2).max(1) as f64),
+    };
+    export::write(path, &Grid::new(width, height, data), color, None)
 }
 
 fn get_input() -> Vec<Segment> {
-    let input = fs::read_to_string("inputs/05.txt").unwrap();
-    input
-        .lines()
-        .map(|line| Segment::from_str(line))
-        // uses the FromIter
-        .collect::<Result<Vec<Segment>, String>>()
-        .unwrap()
+    let input = helpers::read_input(5);
+    helpers::lines(&input, Segment::from_str).unwrap()
 }
 
-fn part1() {
+fn part1() -> usize {
     let segments = get_input();
     let segments = segments
         .into_iter()
@@ -108,10 +137,10 @@ fn part1() {
         }
     }
 
-    println!("part1: {}", overlaps.len());
+    overlaps.len()
 }
 
-fn part2() {
+fn part2() -> usize {
     let segments = get_input();
 
     let mut overlaps: Vec<Point> = vec![];
@@ -126,5 +155,5 @@ fn part2() {
         }
     }
 
-    println!("part2: {}", overlaps.len());
+    overlaps.len()
 }
@@ -1,19 +1,15 @@
-use std::fs;
+use aoc_2021::helpers::{self, list, number};
+use aoc_2021::runner::Runner;
 
 fn get_input() -> Vec<u8> {
-    let input = fs::read_to_string("inputs/06.txt").unwrap();
-    input
-        .lines()
-        .next()
-        .unwrap()
-        .split(',')
-        .map(|value| value.parse().unwrap())
-        .collect()
+    let input = helpers::read_input(6);
+    helpers::line(&input, |line| list(line, ",", number)).unwrap()
 }
 
 fn main() {
-    part1();
-    part2();
+    let runner = Runner::from_args(6);
+    runner.part(1, part1);
+    runner.part(2, part2);
 }
 
 fn solve(days: u32) -> u64 {
@@ -33,10 +29,10 @@ fn solve(days: u32) -> u64 {
     sim.iter().sum::<u64>()
 }
 
-fn part1() {
-    println!("part1: {}", solve(80));
+fn part1() -> u64 {
+    solve(80)
 }
 
-fn part2() {
-    println!("part2: {}", solve(256));
+fn part2() -> u64 {
+    solve(256)
 }
@@ -1,19 +1,15 @@
-use std::fs;
+use aoc_2021::helpers::{self, list, number};
+use aoc_2021::runner::Runner;
 
 fn get_input() -> Vec<i32> {
-    let input = fs::read_to_string("inputs/07.txt").unwrap();
-    input
-        .lines()
-        .next()
-        .unwrap()
-        .split(',')
-        .map(|value| value.parse().unwrap())
-        .collect()
+    let input = helpers::read_input(7);
+    helpers::line(&input, |line| list(line, ",", number)).unwrap()
 }
 
 fn main() {
-    part1();
-    part2();
+    let runner = Runner::from_args(7);
+    runner.part(1, part1);
+    runner.part(2, part2);
 }
 
 fn solve<F>(left_bound: i32, right_bound: i32, f: F) -> i64
@@ -38,7 +34,7 @@ where
     f(left_bound)
 }
 
-fn part1() {
+fn part1() -> i64 {
     let positions = get_input();
     let left_bound = *positions.iter().min().unwrap();
     let right_bound = *positions.iter().max().unwrap();
@@ -48,14 +44,14 @@ fn part1() {
             .map(|position| (target - position).abs() as i64)
             .sum()
     });
-    println!("part1: {}", solution);
+    solution
 }
 
 fn cost(i: i64) -> i64 {
     i * (i + 1) / 2
 }
 
-fn part2() {
+fn part2() -> i64 {
     let positions = get_input();
     let left_bound = *positions.iter().min().unwrap();
     let right_bound = *positions.iter().max().unwrap();
@@ -65,5 +61,5 @@ fn part2() {
             .map(|position| cost((target - position).abs() as i64))
             .sum()
     });
-    println!("part2: {}", solution);
+    solution
 }
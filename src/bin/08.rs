@@ -1,9 +1,11 @@
+use aoc_2021::helpers::{self, split_pair};
+use aoc_2021::runner::Runner;
 use std::collections::HashSet;
-use std::fs;
 
 fn main() {
-    part1();
-    part2();
+    let runner = Runner::from_args(8);
+    runner.part(1, part1);
+    runner.part(2, part2);
 }
 
 fn make_set(s: &str) -> HashSet<char> {
@@ -14,30 +16,20 @@ fn make_set(s: &str) -> HashSet<char> {
     set
 }
 
-fn get_input() -> Vec<(Vec<HashSet<char>>, Vec<HashSet<char>>)> {
-    let input = fs::read_to_string("inputs/08.txt").unwrap();
-    input
-        .lines()
-        .map(|line| {
-            let mut parts = line.split(" | ");
-            let patterns = parts.next().unwrap();
-            let values = parts.next().unwrap();
-
-            let patterns = patterns
-                .split_whitespace()
-                .map(|word| make_set(&word))
-                .collect::<Vec<HashSet<char>>>();
-            let values = values
-                .split_whitespace()
-                .map(|word| make_set(&word))
-                .collect::<Vec<HashSet<char>>>();
-
-            (patterns, values)
-        })
-        .collect()
+type Entry = (Vec<HashSet<char>>, Vec<HashSet<char>>);
+
+fn get_input() -> Vec<Entry> {
+    let input = helpers::read_input(8);
+    helpers::lines(&input, |line| {
+        let (patterns, values) = split_pair(line, " | ")?;
+        let patterns = patterns.split_whitespace().map(make_set).collect();
+        let values = values.split_whitespace().map(make_set).collect();
+        Ok((patterns, values))
+    })
+    .unwrap()
 }
 
-fn part1() {
+fn part1() -> u32 {
     let input = get_input();
 
     let mut total = 0;
@@ -52,10 +44,10 @@ fn part1() {
             }
         }
     }
-    println!("part1: {}", total);
+    total
 }
 
-fn part2() {
+fn part2() -> usize {
     let input = get_input();
 
     let mut total = 0;
@@ -115,5 +107,5 @@ fn part2() {
         total += result;
     }
 
-    println!("part2: {}", total);
+    total
 }
@@ -1,4 +1,9 @@
-use std::fs;
+use aoc_2021::export::{self, Overlay};
+use aoc_2021::grid::GridView;
+use aoc_2021::helpers::{self, digits};
+use aoc_2021::render::{ColorMap, Glyph, Renderer};
+use aoc_2021::runner::Runner;
+use std::env;
 
 struct Grid {
     data: Vec<u8>,
@@ -33,33 +38,133 @@ impl Grid {
     }
 }
 
-fn get_input() -> Grid {
-    let input = fs::read_to_string("inputs/09.txt").unwrap();
-    let mut height = 0;
-    let mut width = 0;
-    let mut data: Vec<u8> = vec![];
-    for line in input.lines() {
-        for c in line.chars() {
-            data.push(c.to_digit(10).unwrap() as u8);
-        }
-        if width == 0 {
-            width = data.len() as i32;
+impl GridView for Grid {
+    type Item = u8;
+
+    fn width(&self) -> usize {
+        self.width as usize
+    }
+
+    fn height(&self) -> usize {
+        self.height as usize
+    }
+
+    fn get(&self, pos: (isize, isize)) -> Option<u8> {
+        if self.contains(pos) {
+            Some(self.unbounded_get((pos.0 as i32, pos.1 as i32)))
+        } else {
+            None
         }
-        height += 1;
     }
+}
+
+fn get_input() -> Grid {
+    let input = helpers::read_input(9);
+    let rows = helpers::lines(&input, digits).unwrap();
     Grid {
-        data,
-        width,
-        height,
+        width: rows.first().map_or(0, Vec::len) as i32,
+        height: rows.len() as i32,
+        data: rows.concat(),
     }
 }
 
 fn main() {
-    part1();
-    part2();
+    let runner = Runner::from_args(9);
+    runner.part(1, part1);
+    runner.part(2, part2);
+
+    // cargo run --bin 09 -- --visualize
+    let args: Vec<String> = env::args().skip(1).collect();
+    if let Some(renderer) = Renderer::from_args(&args).unwrap() {
+        visualize(&renderer);
+    }
+
+    // cargo run --bin 09 -- --export <path.pbm|ppm|svg>
+    if let Some(i) = args.iter().position(|arg| arg == "--export") {
+        export_basins(args.get(i + 1).expect("--export needs a path")).unwrap();
+    }
+}
+
+// labels each cell with the basin it belongs to, numbered in reading order,
+// or `None` for the ridges of height 9.
+fn label_basins(grid: &Grid) -> Vec<Option<usize>> {
+    let mut labels = vec![None; grid.data.len()];
+    let mut count = 0;
+    for start in 0..grid.data.len() {
+        if grid.data[start] == 9 || labels[start].is_some() {
+            continue;
+        }
+        let mut stack = vec![start];
+        labels[start] = Some(count);
+        while let Some(i) = stack.pop() {
+            let pos = (
+                i as isize % grid.width as isize,
+                i as isize / grid.width as isize,
+            );
+            for (x, y) in grid.neighbors(pos) {
+                let j = (x + y * grid.width as isize) as usize;
+                if grid.data[j] != 9 && labels[j].is_none() {
+                    labels[j] = Some(count);
+                    stack.push(j);
+                }
+            }
+        }
+        count += 1;
+    }
+    labels
+}
+
+// the three largest basins in warm colours, the rest shaded by height, with
+// the low points marked.
+fn export_basins(path: &str) -> Result<(), String> {
+    const LARGEST: [export::Rgb; 3] = [(220, 60, 30), (240, 140, 30), (240, 210, 60)];
+    let grid = get_input();
+    let labels = label_basins(&grid);
+    let mut sizes = vec![0; labels.iter().flatten().max().map_or(0, |l| l + 1)];
+    for label in labels.iter().flatten() {
+        sizes[*label] += 1;
+    }
+    let mut largest: Vec<usize> = (0..sizes.len()).collect();
+    largest.sort_by_key(|&label| std::cmp::Reverse(sizes[label]));
+    largest.truncate(3);
+
+    let mut low_points = vec![];
+    for y in 0..grid.height {
+        for x in 0..grid.width {
+            if grid.is_low_point((x, y)) {
+                low_points.push((x as isize, y as isize));
+            }
+        }
+    }
+    let overlay = Overlay {
+        color: export::WHITE,
+        points: low_points,
+        paths: vec![],
+    };
+    let color = |(x, y): (isize, isize), height: u8| {
+        let label = labels[(x + y * grid.width as isize) as usize];
+        match label.and_then(|label| largest.iter().position(|&l| l == label)) {
+            Some(rank) => LARGEST[rank],
+            None if height == 9 => (40, 40, 40),
+            None => ColorMap::Terrain.rgb(height as f64 / 9.0),
+        }
+    };
+    export::write(path, &grid, color, Some(&overlay))
+}
+
+fn visualize(renderer: &Renderer) {
+    let grid = get_input();
+    let frame = renderer.draw(&grid, ColorMap::Terrain, |(x, y), height| {
+        if grid.is_low_point((x as i32, y as i32)) {
+            Glyph::plain('*')
+        } else {
+            Glyph::colored(char::from(b'0' + height), height as f64 / 9.0)
+        }
+    });
+    renderer.show("heightmap, low points as *", &frame);
 }
 
-fn part1() {
+fn part1() -> u64 {
     let grid = get_input();
     let mut total: u64 = 0;
     for y in 0..grid.height {
@@ -71,7 +176,7 @@ fn part1() {
             }
         }
     }
-    println!("part1: {}", total);
+    total
 }
 
 fn explore_basin(grid: &mut Grid, pos: (i32, i32)) -> u64 {
@@ -89,7 +194,7 @@ fn explore_basin(grid: &mut Grid, pos: (i32, i32)) -> u64 {
     }
 }
 
-fn part2() {
+fn part2() -> u64 {
     let mut grid = get_input();
     let mut sizes: Vec<u64> = vec![];
     for y in 0..grid.height {
@@ -102,6 +207,5 @@ fn part2() {
         }
     }
     sizes.sort();
-    let total = sizes.iter().rev().take(3).fold(1, |acc, s| acc * s);
-    println!("part2: {:?}", total);
+    sizes.iter().rev().take(3).product::<u64>()
 }
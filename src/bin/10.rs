@@ -1,14 +1,16 @@
+use aoc_2021::helpers;
+use aoc_2021::runner::Runner;
 use std::collections::HashMap;
-use std::fs;
 
 fn get_input() -> Vec<Vec<char>> {
-    let input = fs::read_to_string("inputs/10.txt").unwrap();
-    input.lines().map(|line| line.chars().collect()).collect()
+    let input = helpers::read_input(10);
+    helpers::lines(&input, |line| Ok(line.chars().collect())).unwrap()
 }
 
 fn main() {
-    part1();
-    part2();
+    let runner = Runner::from_args(10);
+    runner.part(1, part1);
+    runner.part(2, part2);
 }
 
 fn get_mapping() -> HashMap<char, char> {
@@ -22,7 +24,7 @@ fn get_mapping() -> HashMap<char, char> {
     mapping
 }
 
-fn part1() {
+fn part1() -> i32 {
     let input = get_input();
 
     let mapping = get_mapping();
@@ -53,10 +55,10 @@ fn part1() {
         }
     }
 
-    println!("part1: {}", total);
+    total
 }
 
-fn part2() {
+fn part2() -> i64 {
     let input = get_input();
 
     let mapping = get_mapping();
@@ -97,6 +99,5 @@ fn part2() {
     }
 
     scores.sort();
-    let total = scores[scores.len() / 2];
-    println!("part2: {}", total);
+    scores[scores.len() / 2]
 }
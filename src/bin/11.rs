@@ -1,4 +1,8 @@
-use std::fs;
+use aoc_2021::grid::GridView;
+use aoc_2021::helpers::{self, digits};
+use aoc_2021::render::{ColorMap, Glyph, Renderer};
+use aoc_2021::runner::Runner;
+use std::env;
 
 struct Grid {
     data: Vec<i32>,
@@ -65,42 +69,81 @@ impl Grid {
     }
 }
 
-fn get_input() -> Grid {
-    let input = fs::read_to_string("inputs/11.txt").unwrap();
-    let mut data: Vec<i32> = vec![];
-    let mut width = 0;
-    let mut height = 0;
-    for line in input.lines() {
-        for c in line.chars() {
-            data.push(c.to_digit(10).unwrap() as i32);
-        }
-        if width == 0 {
-            width = line.len() as isize;
+impl GridView for Grid {
+    type Item = i32;
+
+    fn width(&self) -> usize {
+        self.width as usize
+    }
+
+    fn height(&self) -> usize {
+        self.height as usize
+    }
+
+    fn get(&self, pos: (isize, isize)) -> Option<i32> {
+        if self.contains(pos) {
+            let (x, y) = pos;
+            Some(self.data[(x + y * self.width) as usize])
+        } else {
+            None
         }
-        height += 1;
     }
+}
+
+fn get_input() -> Grid {
+    let input = helpers::read_input(11);
+    let rows = helpers::lines(&input, digits).unwrap();
     Grid {
-        data,
-        width,
-        height,
+        width: rows.first().map_or(0, Vec::len) as isize,
+        height: rows.len() as isize,
+        data: rows.concat().into_iter().map(i32::from).collect(),
     }
 }
 
 fn main() {
-    part1();
-    part2();
+    let runner = Runner::from_args(11);
+    runner.part(1, part1);
+    runner.part(2, part2);
+
+    // cargo run --bin 11 -- --visualize [--delay <ms>]
+    let args: Vec<String> = env::args().skip(1).collect();
+    if let Some(renderer) = Renderer::from_args(&args).unwrap() {
+        visualize(&renderer);
+    }
+}
+
+// animates every step up to the first where all the octopuses flash, with
+// the ones that just flashed drawn brightest.
+fn visualize(renderer: &Renderer) {
+    let mut grid = get_input();
+    let mut step = 0;
+    loop {
+        step += 1;
+        let flashes = grid.step();
+        let frame = renderer.draw(&grid, ColorMap::Heat, |_, energy| {
+            if energy == 0 {
+                Glyph::colored('*', 1.0)
+            } else {
+                Glyph::colored(char::from(b'0' + energy as u8), energy as f64 / 12.0)
+            }
+        });
+        renderer.show(&format!("step {}: {} flashes", step, flashes), &frame);
+        if flashes == grid.data.len() as u32 {
+            break;
+        }
+    }
 }
 
-fn part1() {
+fn part1() -> u32 {
     let mut grid = get_input();
     let mut total = 0;
     for _ in 0..100 {
         total += grid.step();
     }
-    println!("part1: {}", total);
+    total
 }
 
-fn part2() {
+fn part2() -> u32 {
     let mut grid = get_input();
     let mut step = 0;
     loop {
@@ -109,5 +152,5 @@ fn part2() {
             break;
         }
     }
-    println!("part2: {}", step);
+    step
 }
@@ -1,15 +1,13 @@
+use aoc_2021::helpers::{self, split_pair};
+use aoc_2021::runner::Runner;
 use std::collections::{HashMap, HashSet};
-use std::fs;
 
 type Graph = HashMap<String, HashSet<String>>;
 
 fn get_input() -> Graph {
-    let input = fs::read_to_string("inputs/12.txt").unwrap();
+    let input = helpers::read_input(12);
     let mut graph: Graph = HashMap::new();
-    for line in input.lines() {
-        let mut parts = line.split('-');
-        let from = parts.next().unwrap();
-        let to = parts.next().unwrap();
+    for (from, to) in helpers::lines(&input, |line| split_pair(line, "-")).unwrap() {
         let from_entry = graph.entry(from.to_string()).or_default();
         from_entry.insert(to.to_string());
         let to_entry = graph.entry(to.to_string()).or_default();
@@ -19,8 +17,9 @@ fn get_input() -> Graph {
 }
 
 fn main() {
-    part1();
-    part2();
+    let runner = Runner::from_args(12);
+    runner.part(1, part1);
+    runner.part(2, part2);
 }
 
 fn find_paths<'a>(
@@ -59,22 +58,22 @@ fn find_paths<'a>(
     }
 }
 
-fn part1() {
+fn part1() -> usize {
     let graph = get_input();
     let start = match graph.keys().find(|key| *key == "start") {
         Some(end) => end,
         None => panic!("start not found?"),
     };
     let paths = find_paths(&graph, start, Default::default(), Default::default(), false);
-    println!("path1: {}", paths.len());
+    paths.len()
 }
 
-fn part2() {
+fn part2() -> usize {
     let graph = get_input();
     let start = match graph.keys().find(|key| *key == "start") {
         Some(end) => end,
         None => panic!("start not found?"),
     };
     let paths = find_paths(&graph, start, Default::default(), Default::default(), true);
-    println!("path2: {}", paths.len());
+    paths.len()
 }
@@ -1,97 +1,100 @@
+use aoc_2021::export;
+use aoc_2021::grid::{Grid, GridView};
+use aoc_2021::helpers::{self, number, pair, split_pair};
+use aoc_2021::runner::Runner;
 use std::collections::HashSet;
-use std::fs;
+use std::env;
 
-fn get_input() -> (Vec<(i32, i32)>, HashSet<(i32, i32)>) {
-    let input = fs::read_to_string("inputs/13.txt").unwrap();
-    let mut lines = input.lines();
-    let mut folds: Vec<(i32, i32)> = Default::default();
-    let mut points: HashSet<(i32, i32)> = Default::default();
-    while let Some(line) = lines.next() {
-        if line.is_empty() {
-            break;
-        }
-        let mut parts = line.split(',');
-        let x: i32 = parts.next().unwrap().parse().unwrap();
-        let y: i32 = parts.next().unwrap().parse().unwrap();
-        points.insert((x, y));
-    }
-    while let Some(line) = lines.next() {
-        let parts = line.split_whitespace().skip(2).next().unwrap();
-        let mut parts = parts.split('=');
-        let axis = parts.next().unwrap();
-        let value: i32 = parts.next().unwrap().parse().unwrap();
-        let fold = match axis {
-            "x" => (value, 0),
-            "y" => (0, value),
-            _ => panic!("unexpected char: {}", axis),
-        };
-        folds.push(fold);
-    }
-    (folds, points)
+type Point = (i32, i32);
+
+fn get_input() -> (Vec<Point>, HashSet<Point>) {
+    let input = helpers::read_input(13);
+    let sections = helpers::sections(&input);
+    let [points, folds] = sections[..] else {
+        panic!("expected dots and folds separated by a blank line");
+    };
+    let points = points.parse_lines(|line| pair(line, ",", number)).unwrap();
+    let folds = folds
+        .parse_lines(|line| {
+            let fold = line
+                .strip_prefix("fold along ")
+                .ok_or("expected 'fold along'")?;
+            let (axis, value) = split_pair(fold, "=")?;
+            match (axis, number(value)?) {
+                ("x", value) => Ok((value, 0)),
+                ("y", value) => Ok((0, value)),
+                _ => Err(format!("unexpected axis '{}'", axis)),
+            }
+        })
+        .unwrap();
+    (folds, points.into_iter().collect())
 }
 
 fn main() {
-    part1();
-    part2();
-}
+    let runner = Runner::from_args(13);
+    runner.part(1, part1);
+    runner.part(2, part2);
 
-fn part1() {
-    let (folds, points) = get_input();
-    let mut transformed_points: HashSet<(i32, i32)> = Default::default();
-    for (mut px, mut py) in points {
-        for (fx, fy) in folds.iter().cloned().take(1) {
-            if fx != 0 && px > fx {
-                px = ((px - fx) * -1) + fx
-            }
-            if fy != 0 && py > fy {
-                py = ((py - fy) * -1) + fy
-            }
-        }
-        transformed_points.insert((px, py));
+    // cargo run --bin 13 -- --export <path.pbm|ppm|svg> for the folded paper.
+    let args: Vec<String> = env::args().skip(1).collect();
+    if let Some(i) = args.iter().position(|arg| arg == "--export") {
+        let path = args.get(i + 1).expect("--export needs a path");
+        let paper = fold_paper(usize::MAX);
+        export::write(
+            path,
+            &paper,
+            |_, dot| if dot { export::BLACK } else { export::WHITE },
+            None,
+        )
+        .unwrap();
     }
-    println!("part1: {}", transformed_points.len());
 }
 
-fn part2() {
+// the paper after the first `count` folds, with a dot wherever a point
+// landed.
+fn fold_paper(count: usize) -> Grid<bool> {
     let (folds, points) = get_input();
-    let mut transformed_points: HashSet<(i32, i32)> = Default::default();
+    let mut folded: HashSet<Point> = Default::default();
     for (mut px, mut py) in points {
-        for (fx, fy) in folds.iter().cloned() {
+        for &(fx, fy) in folds.iter().take(count) {
             if fx != 0 && px > fx {
-                px = ((px - fx) * -1) + fx
+                px = 2 * fx - px;
             }
             if fy != 0 && py > fy {
-                py = ((py - fy) * -1) + fy
+                py = 2 * fy - py;
             }
         }
-        transformed_points.insert((px, py));
-    }
-
-    let mut mx = 0;
-    let mut my = 0;
-    for &(px, py) in &transformed_points {
-        if px > mx {
-            mx = px;
-        }
-        if py > my {
-            my = py;
-        }
+        folded.insert((px, py));
     }
-    let mut display: Vec<Vec<char>> = Default::default();
-    for _ in 0..=my {
-        let mut line: Vec<char> = vec![];
-        for _ in 0..=mx {
-            line.push(' ');
-        }
-        display.push(line);
+    let width = folded.iter().map(|&(x, _)| x + 1).max().unwrap_or(0) as usize;
+    let height = folded.iter().map(|&(_, y)| y + 1).max().unwrap_or(0) as usize;
+    let mut data = vec![false; width * height];
+    for (x, y) in folded {
+        data[x as usize + y as usize * width] = true;
     }
+    Grid::new(width, height, data)
+}
 
-    for (x, y) in transformed_points {
-        display[y as usize][x as usize] = '#';
-    }
+fn dots(paper: &Grid<bool>) -> Vec<String> {
+    (0..paper.height() as isize)
+        .map(|y| {
+            (0..paper.width() as isize)
+                .map(|x| if paper.get((x, y)).unwrap() { '#' } else { ' ' })
+                .collect()
+        })
+        .collect()
+}
 
-    println!("part2:");
-    for line in display {
-        println!("{}", String::from_iter(line));
-    }
+fn part1() -> usize {
+    let paper = fold_paper(1);
+    let total: usize = dots(&paper)
+        .iter()
+        .map(|line| line.matches('#').count())
+        .sum();
+    total
+}
+
+fn part2() -> String {
+    let paper = fold_paper(usize::MAX);
+    dots(&paper).join("\n")
 }
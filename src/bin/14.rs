@@ -1,25 +1,40 @@
+use aoc_2021::helpers::{self, rule};
+use aoc_2021::runner::Runner;
 use std::collections::HashMap;
-use std::fs;
 
 fn get_input() -> (Vec<char>, HashMap<(char, char), char>) {
-    let input = fs::read_to_string("inputs/14.txt").unwrap();
-    let mut lines = input.lines();
-    let template = lines.next().unwrap();
-    let template: Vec<char> = template.chars().collect();
-    let mut rules: HashMap<(char, char), char> = HashMap::new();
-    for line in lines.skip(1) {
-        let mut parts = line.split(" -> ");
-        let mut from = parts.next().unwrap().chars();
-        let to = parts.next().unwrap().chars().next().unwrap();
-        let from = (from.next().unwrap(), from.next().unwrap());
-        rules.insert(from, to);
+    let input = helpers::read_input(14);
+    let sections = helpers::sections(&input);
+    let [template, rules] = sections[..] else {
+        panic!("expected a template and rules separated by a blank line");
+    };
+    let template = template
+        .parse_line(|line| Ok(line.chars().collect()))
+        .unwrap();
+    let rules = rules
+        .parse_lines(|line| rule(line, pair_of_chars, single_char))
+        .unwrap();
+    (template, rules.into_iter().collect())
+}
+
+fn pair_of_chars(s: &str) -> Result<(char, char), String> {
+    match s.chars().collect::<Vec<char>>()[..] {
+        [a, b] => Ok((a, b)),
+        _ => Err(format!("expected two elements, found '{}'", s)),
+    }
+}
+
+fn single_char(s: &str) -> Result<char, String> {
+    match s.chars().collect::<Vec<char>>()[..] {
+        [c] => Ok(c),
+        _ => Err(format!("expected one element, found '{}'", s)),
     }
-    (template, rules)
 }
 
 fn main() {
-    println!("part1: {:?}", solve(10));
-    println!("part2: {:?}", solve(40));
+    let runner = Runner::from_args(14);
+    runner.part(1, || solve(10));
+    runner.part(2, || solve(40));
 }
 
 fn solve(steps: u32) -> u64 {
@@ -1,36 +1,17 @@
+use aoc_2021::export::{self, Overlay};
+use aoc_2021::grid::{Grid, GridView, TiledGrid};
+use aoc_2021::helpers::{self, digits};
+use aoc_2021::render::{ColorMap, Glyph, Renderer};
+use aoc_2021::runner::Runner;
 use std::cmp::Ordering;
-use std::collections::{BinaryHeap, HashSet};
-use std::fs;
-
-struct Grid {
-    width: isize,
-    height: isize,
-    data: Vec<u16>,
-}
-
-impl Grid {
-    fn unbounded_get(&self, scale: u8, pos: (isize, isize)) -> u16 {
-        let scaled_width = scale as isize * self.width;
-        let scaled_height = scale as isize * self.height;
-        let (x, y) = pos;
-        if x >= 0 && x < scaled_width && y >= 0 && y < scaled_height {
-            let scalar = (x / self.width + y / self.height) as u16;
-            let value = self.data[(self.width * (y % self.height) + (x % self.width)) as usize];
-            let mut scaled_value = value + scalar;
-            if scaled_value > 9 {
-                scaled_value %= 9;
-            }
-            scaled_value
-        } else {
-            u16::MAX
-        }
-    }
-}
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::env;
 
 #[derive(Eq, PartialEq, Copy, Clone)]
 struct Path {
     cost: u32,
     pos: (isize, isize),
+    from: (isize, isize),
 }
 
 impl Ord for Path {
@@ -39,6 +20,7 @@ impl Ord for Path {
             .cost
             .cmp(&self.cost)
             .then_with(|| self.pos.cmp(&other.pos))
+            .then_with(|| self.from.cmp(&other.from))
     }
 }
 
@@ -48,76 +30,145 @@ impl PartialOrd for Path {
     }
 }
 
-fn get_input() -> Grid {
-    let input = fs::read_to_string("inputs/15.txt").unwrap();
-    let mut data = vec![];
-    let mut width = 0;
-    let mut height = 0;
-    for line in input.lines() {
-        for cell in line.chars() {
-            data.push(cell.to_digit(10).unwrap() as u16);
-        }
-        if width == 0 {
-            width = line.chars().count() as isize;
-        }
-        height += 1;
+fn get_input() -> Grid<u16> {
+    let input = helpers::read_input(15);
+    let rows = helpers::lines(&input, digits).unwrap();
+    let width = rows.first().map_or(0, Vec::len);
+    let data = rows.concat().into_iter().map(u16::from).collect();
+    Grid::new(width, rows.len(), data)
+}
+
+// risk levels above 9 wrap back around to 1.
+fn wrap_risk(value: u16, tile: (usize, usize)) -> u16 {
+    let (tile_x, tile_y) = tile;
+    (value + (tile_x + tile_y) as u16 - 1) % 9 + 1
+}
+
+fn main() {
+    let runner = Runner::from_args(15);
+    runner.part(1, part1);
+    runner.part(2, part2);
+
+    // cargo run --bin 15 -- --visualize [--delay <ms>]
+    let args: Vec<String> = env::args().skip(1).collect();
+    if let Some(renderer) = Renderer::from_args(&args).unwrap() {
+        visualize(&renderer);
     }
-    Grid {
-        width,
-        height,
-        data,
+
+    // cargo run --bin 15 -- --export <path.pbm|ppm|svg>
+    if let Some(i) = args.iter().position(|arg| arg == "--export") {
+        export_path(args.get(i + 1).expect("--export needs a path")).unwrap();
     }
 }
 
-fn main() {
-    part1();
-    part2();
+fn solve<G>(grid: &G) -> u32
+where
+    G: GridView<Item = u16>,
+{
+    search(grid, |_, _| {}).0
 }
 
-fn solve(grid: Grid, scale: u8) -> u32 {
-    let neighbors = [(0, 1), (0, -1), (1, 0), (-1, 0)];
-    let mut visited: HashSet<(isize, isize)> = HashSet::new();
+// Dijkstra from the top left to the bottom right, returning the lowest cost
+// and a path with that cost. `observe` is called with the settled positions,
+// each mapped to the one it was reached from, and the frontier each time a
+// position is settled.
+fn search<G, F>(grid: &G, mut observe: F) -> (u32, Vec<(isize, isize)>)
+where
+    G: GridView<Item = u16>,
+    F: FnMut(&HashMap<(isize, isize), (isize, isize)>, &BinaryHeap<Path>),
+{
+    let mut visited: HashMap<(isize, isize), (isize, isize)> = HashMap::new();
     let mut heap: BinaryHeap<Path> = BinaryHeap::new();
 
     let start_pos = (0, 0);
-    let end_pos = (
-        (grid.width * scale as isize) - 1,
-        (grid.height * scale as isize) - 1,
-    );
+    let end_pos = (grid.width() as isize - 1, grid.height() as isize - 1);
     heap.push(Path {
         cost: 0,
         pos: start_pos,
+        from: start_pos,
     });
 
     let mut lowest_cost = 0;
     while let Some(current) = heap.pop() {
-        if visited.contains(&current.pos) {
+        if visited.contains_key(&current.pos) {
             continue;
         }
-        visited.insert(current.pos);
+        visited.insert(current.pos, current.from);
+        observe(&visited, &heap);
         if current.pos == end_pos {
             lowest_cost = current.cost;
             break;
         }
-        for (offset_x, offset_y) in neighbors {
-            let (x, y) = current.pos;
-            let next_pos = (x + offset_x, y + offset_y);
-            let next_cost = current.cost + grid.unbounded_get(scale, next_pos) as u32;
+        for next_pos in grid.neighbors(current.pos) {
+            if visited.contains_key(&next_pos) {
+                continue;
+            }
+            let next_cost = current.cost + grid.get(next_pos).unwrap() as u32;
             heap.push(Path {
                 cost: next_cost,
                 pos: next_pos,
+                from: current.pos,
             });
         }
     }
-    lowest_cost
+
+    let mut path = vec![end_pos];
+    while let Some(&from) = visited.get(path.last().unwrap()) {
+        if from == *path.last().unwrap() {
+            break;
+        }
+        path.push(from);
+    }
+    path.reverse();
+    (lowest_cost, path)
+}
+
+// animates the search over the part 1 map, a frame per row's worth of
+// settled positions, with the frontier drawn brightest.
+fn visualize(renderer: &Renderer) {
+    let grid = get_input();
+    let every = grid.width();
+    let (lowest_cost, _) = search(&grid, |visited, heap| {
+        if visited.len() % every != 0 {
+            return;
+        }
+        let frontier: HashSet<(isize, isize)> = heap.iter().map(|path| path.pos).collect();
+        let frame = renderer.draw(&grid, ColorMap::Heat, |pos, risk| {
+            let ch = char::from_digit(risk as u32, 10).unwrap();
+            if frontier.contains(&pos) {
+                Glyph::colored('+', 1.0)
+            } else if visited.contains_key(&pos) {
+                Glyph::colored(ch, 0.4)
+            } else {
+                Glyph::plain(ch)
+            }
+        });
+        renderer.show(&format!("{} positions settled", visited.len()), &frame);
+    });
+    println!("lowest total risk: {}", lowest_cost);
+}
+
+// the part 1 risk map shaded from low to high risk, with the safest path
+// drawn over it.
+fn export_path(path: &str) -> Result<(), String> {
+    let grid = get_input();
+    let (_, route) = search(&grid, |_, _| {});
+    let overlay = Overlay {
+        color: (30, 90, 220),
+        points: vec![],
+        paths: vec![route],
+    };
+    let color = |_, risk: u16| ColorMap::Heat.rgb(1.0 - risk as f64 / 12.0);
+    export::write(path, &grid, color, Some(&overlay))
 }
 
-fn part1() {
+fn part1() -> u32 {
     let grid = get_input();
-    println!("part1: {}", solve(grid, 1));
+    solve(&grid)
 }
 
-fn part2() {
+fn part2() -> u32 {
     let grid = get_input();
-    println!("part2: {}", solve(grid, 5));
+    let tiled = TiledGrid::new(&grid, 5, 5, wrap_risk);
+    solve(&tiled)
 }
@@ -1,45 +1,203 @@
-use std::cmp::max;
-
-fn sim(vel: (i32, i32)) -> Option<i32> {
-    let top_left = (244, -54);
-    let bottom_right = (303, -91);
-
-    let (mut v_x, mut v_y) = vel;
-    let mut p_x = 0;
-    let mut p_y = 0;
-    let mut h_y = i32::MIN;
-    loop {
-        p_x += v_x;
-        p_y += v_y;
-        h_y = max(p_y, h_y);
-        if v_x > 0 {
-            v_x -= 1;
-        } else if v_x < 0 {
-            v_x += 1;
+use aoc_2021::parallel;
+use aoc_2021::runner::Runner;
+use std::collections::{BTreeMap, BTreeSet};
+use std::env;
+
+#[derive(Debug, Clone, Copy)]
+struct Target {
+    x: (i32, i32),
+    y: (i32, i32),
+}
+
+impl Target {
+    fn contains_x(&self, x: i32) -> bool {
+        x >= self.x.0 && x <= self.x.1
+    }
+
+    fn contains_y(&self, y: i32) -> bool {
+        y >= self.y.0 && y <= self.y.1
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct Shot {
+    velocity: (i32, i32),
+    apex: i32,
+}
+
+// velocities along one axis, grouped by the step they're inside the target.
+type ByStep = BTreeMap<i32, Vec<i32>>;
+
+fn tri(n: i32) -> i32 {
+    n * (n + 1) / 2
+}
+
+// x velocities that land in the target on each step while still moving, plus
+// the ones that come to rest inside it along with the step they stop on.
+fn x_velocities(target: &Target) -> (ByStep, Vec<(i32, i32)>) {
+    let mut moving: ByStep = BTreeMap::new();
+    let mut resting = vec![];
+    // anything faster overshoots on the first step.
+    for v_x in target.x.0.min(0)..=target.x.1.max(0) {
+        let speed = v_x.abs();
+        let mut p_x = 0;
+        for step in 1..speed {
+            p_x += v_x.signum() * (speed - step + 1);
+            if target.contains_x(p_x) {
+                moving.entry(step).or_default().push(v_x);
+            }
         }
-        v_y -= 1;
+        if target.contains_x(v_x.signum() * tri(speed)) {
+            resting.push((v_x, speed.max(1)));
+        }
+    }
+    (moving, resting)
+}
 
-        if p_y < bottom_right.1 && v_y < 0 {
-            break None;
+// y velocities that are inside the target on each step.
+fn y_velocities(target: &Target, max_v_y: i32) -> ByStep {
+    let mut by_step: ByStep = BTreeMap::new();
+    for v_y in target.y.0.min(0)..=max_v_y {
+        let mut p_y = 0;
+        let mut step = 0;
+        loop {
+            step += 1;
+            p_y += v_y - (step - 1);
+            if target.contains_y(p_y) {
+                by_step.entry(step).or_default().push(v_y);
+            }
+            let falling = v_y - (step - 1) < 0;
+            if falling && p_y < target.y.0 {
+                break;
+            }
         }
-        if p_x >= top_left.0 && p_x <= bottom_right.0 && p_y <= top_left.1 && p_y >= bottom_right.1
-        {
-            break Some(h_y);
+    }
+    by_step
+}
+
+fn solve(target: &Target, jobs: usize) -> Result<Vec<Shot>, String> {
+    let (moving, resting) = x_velocities(target);
+
+    let mut max_v_y = target.y.0.abs().max(target.y.1.abs());
+    if target.contains_y(0) {
+        // every upward shot comes back through y = 0, so shots that rest
+        // inside the target horizontally would work for any v_y.
+        if !resting.is_empty() {
+            return Err("target contains y = 0 and has infinitely many solutions".to_string());
         }
+        let max_steps = moving.keys().last().copied().unwrap_or(0);
+        max_v_y = max_v_y.max(max_steps / 2);
     }
+
+    // the steps are independent, so they're shared out across `jobs` threads.
+    let by_step: Vec<(i32, Vec<i32>)> = y_velocities(target, max_v_y).into_iter().collect();
+    let found = parallel::map(&by_step, jobs, |(step, v_ys)| {
+        let mut v_xs: Vec<i32> = moving.get(step).cloned().unwrap_or_default();
+        v_xs.extend(
+            resting
+                .iter()
+                .filter(|(_, rest_step)| rest_step <= step)
+                .map(|(v_x, _)| v_x),
+        );
+        let mut shots = vec![];
+        for &v_y in v_ys {
+            for &v_x in &v_xs {
+                shots.push(Shot {
+                    velocity: (v_x, v_y),
+                    apex: tri(v_y.max(0)),
+                });
+            }
+        }
+        shots
+    });
+    let shots: BTreeSet<Shot> = found.into_iter().flatten().collect();
+    Ok(shots.into_iter().collect())
 }
 
 fn main() {
-    let mut t = 0;
-    let mut h = 0;
-    for v_x in -500..500 {
-        for v_y in -500..500 {
-            if let Some(r) = sim((v_x, v_y)) {
-                t += 1;
-                h = max(h, r);
+    let target = Target {
+        x: (244, 303),
+        y: (-91, -54),
+    };
+    let args: Vec<String> = env::args().skip(1).collect();
+    let jobs = parallel::jobs_from_args(&args).unwrap();
+    let runner = Runner::from_args(17);
+    let shots = runner.shared("find shots", || solve(&target, jobs).unwrap());
+    runner.part(1, || shots.iter().map(|shot| shot.apex).max().unwrap());
+    runner.part(2, || shots.len());
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn sim(target: &Target, vel: (i32, i32)) -> bool {
+        let (mut v_x, mut v_y) = vel;
+        let (mut p_x, mut p_y) = (0, 0);
+        for _ in 0..1000 {
+            p_x += v_x;
+            p_y += v_y;
+            v_x -= v_x.signum();
+            v_y -= 1;
+            if target.contains_x(p_x) && target.contains_y(p_y) {
+                return true;
+            }
+        }
+        false
+    }
+
+    #[test]
+    fn test_example() {
+        let target = Target {
+            x: (20, 30),
+            y: (-10, -5),
+        };
+        let shots = solve(&target, 4).unwrap();
+        assert_eq!(shots.len(), 112);
+        assert_eq!(shots.iter().map(|shot| shot.apex).max(), Some(45));
+    }
+
+    #[test]
+    fn test_matches_simulation() {
+        let targets = [
+            Target {
+                x: (20, 30),
+                y: (-10, -5),
+            },
+            Target {
+                x: (-30, -20),
+                y: (-10, -5),
+            },
+            Target {
+                x: (20, 30),
+                y: (5, 10),
+            },
+            Target {
+                x: (-4, 6),
+                y: (-10, -5),
+            },
+            Target {
+                x: (22, 26),
+                y: (-3, 4),
+            },
+        ];
+        for target in &targets {
+            let shots = solve(target, 1).unwrap();
+            let mut expected = vec![];
+            for v_x in -60..60 {
+                for v_y in -60..60 {
+                    if sim(target, (v_x, v_y)) {
+                        expected.push((v_x, v_y));
+                    }
+                }
             }
+            let found: Vec<(i32, i32)> = shots.iter().map(|shot| shot.velocity).collect();
+            assert_eq!(found, expected, "{:?}", target);
         }
+        let stalled = Target {
+            x: (5, 7),
+            y: (-2, 2),
+        };
+        assert!(solve(&stalled, 1).is_err());
     }
-    println!("part1: {}", h);
-    println!("part2: {}", t);
 }
@@ -1,170 +1,441 @@
-use std::fs;
+use aoc_2021::helpers::{self, list, number};
+use aoc_2021::parallel;
+use aoc_2021::runner::Runner;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::env;
+use std::fmt;
+use std::process;
 
-const X: i32 = 1;
-const Y: i32 = 2;
-const Z: i32 = 3;
+type Vec3 = [i32; 3];
 
-fn rot_x(start: [i32; 3]) -> [i32; 3] {
-    let [x, y, z] = start;
-    [x, z, -y]
+// default number of beacons two scanners must share to be considered overlapping.
+const OVERLAP: usize = 12;
+
+/// A proper rotation, stored as a 3x3 integer matrix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Rotation([[i32; 3]; 3]);
+
+impl Rotation {
+    const IDENTITY: Rotation = Rotation([[1, 0, 0], [0, 1, 0], [0, 0, 1]]);
+
+    // the 24 orientations are the signed axis permutations with determinant 1.
+    fn all() -> Vec<Rotation> {
+        let permutations = [
+            [0, 1, 2],
+            [0, 2, 1],
+            [1, 0, 2],
+            [1, 2, 0],
+            [2, 0, 1],
+            [2, 1, 0],
+        ];
+        let mut results = vec![];
+        for permutation in permutations {
+            for signs in 0..8 {
+                let mut matrix = [[0; 3]; 3];
+                for (row, &col) in permutation.iter().enumerate() {
+                    matrix[row][col] = if signs & (1 << row) != 0 { -1 } else { 1 };
+                }
+                let rotation = Rotation(matrix);
+                if rotation.determinant() == 1 {
+                    results.push(rotation);
+                }
+            }
+        }
+        results
+    }
+
+    fn determinant(&self) -> i32 {
+        let m = &self.0;
+        m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+            - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+            + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+    }
+
+    fn apply(&self, pos: &Vec3) -> Vec3 {
+        let mut result = [0; 3];
+        for (value, row) in result.iter_mut().zip(&self.0) {
+            *value = row[0] * pos[0] + row[1] * pos[1] + row[2] * pos[2];
+        }
+        result
+    }
+
+    // applies `other` first, then `self`.
+    fn compose(&self, other: &Rotation) -> Rotation {
+        let mut matrix = [[0; 3]; 3];
+        for (row, values) in matrix.iter_mut().enumerate() {
+            for (col, value) in values.iter_mut().enumerate() {
+                *value = (0..3).map(|k| self.0[row][k] * other.0[k][col]).sum();
+            }
+        }
+        Rotation(matrix)
+    }
+
+    fn inverse(&self) -> Rotation {
+        let mut matrix = [[0; 3]; 3];
+        for (row, values) in matrix.iter_mut().enumerate() {
+            for (col, value) in values.iter_mut().enumerate() {
+                *value = self.0[col][row];
+            }
+        }
+        Rotation(matrix)
+    }
 }
 
-fn rot_y(start: [i32; 3]) -> [i32; 3] {
-    let [x, y, z] = start;
-    [z, y, -x]
+/// Where a scanner sits and which way it faces within some parent frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Pose {
+    position: Vec3,
+    rotation: Rotation,
 }
 
-fn get_transforms() -> Vec<[i32; 3]> {
-    let origin = [X, Y, Z];
-    let bases = [
-        origin,
-        rot_y(origin),
-        rot_y(rot_y(origin)),
-        rot_y(rot_y(rot_y(origin))),
-        rot_y(rot_x(origin)),
-        rot_y(rot_y(rot_y(rot_x(origin)))),
-    ];
+impl Pose {
+    const ORIGIN: Pose = Pose {
+        position: [0, 0, 0],
+        rotation: Rotation::IDENTITY,
+    };
 
-    let mut results = vec![];
-    for base in bases {
-        results.push(base);
-        results.push(rot_x(base));
-        results.push(rot_x(rot_x(base)));
-        results.push(rot_x(rot_x(rot_x(base))));
+    // maps a point from the scanner's frame into the parent frame.
+    fn apply(&self, pos: &Vec3) -> Vec3 {
+        add(&self.position, &self.rotation.apply(pos))
     }
 
-    results
-}
+    // `other` is relative to this scanner, the result is relative to our parent.
+    fn compose(&self, other: &Pose) -> Pose {
+        Pose {
+            position: self.apply(&other.position),
+            rotation: self.rotation.compose(&other.rotation),
+        }
+    }
 
-fn apply_transform(pos: &[i32; 3], transform: &[i32; 3]) -> [i32; 3] {
-    let mut result = [0, 0, 0];
-    result[0] = pos[(transform[0].abs() - 1) as usize];
-    result[1] = pos[(transform[1].abs() - 1) as usize];
-    result[2] = pos[(transform[2].abs() - 1) as usize];
-    if transform[0] < 0 {
-        result[0] *= -1;
+    fn inverse(&self) -> Pose {
+        let rotation = self.rotation.inverse();
+        Pose {
+            position: sub(&[0, 0, 0], &rotation.apply(&self.position)),
+            rotation,
+        }
     }
-    if transform[1] < 0 {
-        result[1] *= -1;
+}
+
+struct Scanner {
+    beacons: Vec<Vec3>,
+    beacon_set: HashSet<Vec3>,
+    // squared distances between every pair of beacons, which don't change
+    // with the scanner's position or orientation.
+    fingerprint: HashMap<i32, usize>,
+}
+
+impl Scanner {
+    fn new(beacons: Vec<Vec3>) -> Self {
+        let mut fingerprint = HashMap::new();
+        for (i, a) in beacons.iter().enumerate() {
+            for b in &beacons[i + 1..] {
+                let d = sub(a, b);
+                *fingerprint
+                    .entry(d[0] * d[0] + d[1] * d[1] + d[2] * d[2])
+                    .or_insert(0) += 1;
+            }
+        }
+        Scanner {
+            beacon_set: beacons.iter().cloned().collect(),
+            beacons,
+            fingerprint,
+        }
     }
-    if transform[2] < 0 {
-        result[2] *= -1;
+
+    fn shared_distances(&self, other: &Scanner) -> usize {
+        self.fingerprint
+            .iter()
+            .map(|(d, count)| (*count).min(*other.fingerprint.get(d).unwrap_or(&0)))
+            .sum()
     }
-    result
 }
 
-fn dist(a: &[i32; 3]) -> i32 {
+fn dist(a: &Vec3) -> i32 {
     a[0] + a[1] + a[2]
 }
 
-fn abs(a: &[i32; 3]) -> [i32; 3] {
+fn abs(a: &Vec3) -> Vec3 {
     [a[0].abs(), a[1].abs(), a[2].abs()]
 }
 
-fn sub(a: &[i32; 3], b: &[i32; 3]) -> [i32; 3] {
+fn sub(a: &Vec3, b: &Vec3) -> Vec3 {
     [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
 }
 
-fn add(a: &[i32; 3], b: &[i32; 3]) -> [i32; 3] {
+fn add(a: &Vec3, b: &Vec3) -> Vec3 {
     [a[0] + b[0], a[1] + b[1], a[2] + b[2]]
 }
 
-fn get_input() -> Vec<Vec<[i32; 3]>> {
-    let input = fs::read_to_string("inputs/19.txt").unwrap();
-    let mut beacons = vec![];
-    let mut scanners = vec![];
-    for line in input.lines() {
-        if line.is_empty() {
-            continue;
-        } else if line.starts_with("---") {
-            if !beacons.is_empty() {
-                scanners.push(beacons);
+fn parse_beacon(line: &str) -> Result<Vec3, String> {
+    let values: Vec<i32> = list(line, ",", number)?;
+    values
+        .try_into()
+        .map_err(|_| "expected three coordinates".to_string())
+}
+
+fn get_input() -> Vec<Scanner> {
+    let input = helpers::read_input(19);
+    helpers::sections(&input)
+        .iter()
+        .map(|section| {
+            let (_, beacons) = section.split_first();
+            Scanner::new(beacons.parse_lines(parse_beacon).unwrap())
+        })
+        .collect()
+}
+
+// finds the pose of `beacons` relative to `origin_beacons` by voting on the
+// offset implied by every pair of beacons under each rotation, with the
+// rotations shared out across `jobs` threads.
+fn find_offset(
+    origin_beacons: &HashSet<Vec3>,
+    beacons: &[Vec3],
+    threshold: usize,
+    jobs: usize,
+) -> Option<Pose> {
+    parallel::find_map_first(&Rotation::all(), jobs, |&rotation| {
+        let rotated: Vec<Vec3> = beacons.iter().map(|b| rotation.apply(b)).collect();
+        let mut votes: HashMap<Vec3, usize> = HashMap::new();
+        for origin_beacon in origin_beacons {
+            for beacon in &rotated {
+                let count = votes.entry(sub(origin_beacon, beacon)).or_insert(0);
+                *count += 1;
+                if *count == threshold {
+                    return Some(Pose {
+                        position: sub(origin_beacon, beacon),
+                        rotation,
+                    });
+                }
             }
-            beacons = vec![];
-        } else {
-            let values: Vec<i32> = line
-                .split(',')
-                .map(|value| value.parse().unwrap())
-                .collect();
-            beacons.push([values[0], values[1], values[2]]);
-        }
-    }
-    if !beacons.is_empty() {
-        scanners.push(beacons);
-    }
-    scanners
-}
-
-fn find_offset(origin_beacons: &[[i32; 3]], beacons: &[[i32; 3]]) -> Option<([i32; 3], [i32; 3])> {
-    let transforms = get_transforms();
-    for transform in &transforms {
-        for beacon in beacons {
-            let transformed_beacon = apply_transform(beacon, transform);
-            for origin_beacon in origin_beacons {
-                let possible_origin = sub(origin_beacon, &transformed_beacon);
-                let mut count = 0;
-                for second_beacon in beacons {
-                    let second_beacon =
-                        add(&possible_origin, &apply_transform(second_beacon, transform));
-                    if origin_beacons.contains(&second_beacon) {
-                        count += 1;
-                        if count == 12 {
-                            return Some((possible_origin, *transform));
-                        }
-                    }
+        }
+        None
+    })
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum AlignError {
+    // fewer than three shared beacons can't pin down an orientation.
+    ThresholdTooLow(usize),
+    // scanner groups that overlap each other but nothing outside the group.
+    // The first group always contains scanner 0.
+    Disconnected(Vec<Vec<usize>>),
+}
+
+impl fmt::Display for AlignError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AlignError::ThresholdTooLow(threshold) => {
+                write!(f, "overlap threshold {} is below 3", threshold)
+            }
+            AlignError::Disconnected(groups) => {
+                write!(f, "scanners form {} disconnected groups:", groups.len())?;
+                for group in groups {
+                    write!(f, " {:?}", group)?;
                 }
+                Ok(())
             }
         }
     }
-    None
 }
 
-fn main() {
-    let mut scanners = get_input().into_iter();
-    let mut origin_beacons = scanners.next().unwrap();
-    let mut scanners: Vec<_> = scanners.collect();
-    let mut scanner_positions = vec![];
-    while !scanners.is_empty() {
-        let mut new_scanners: Vec<Vec<[i32; 3]>> = vec![];
-        for beacons in scanners {
-            match find_offset(&origin_beacons, &beacons) {
-                Some((origin, transform)) => {
-                    for beacon in &beacons {
-                        scanner_positions.push(origin);
-                        let transformed = add(&origin, &apply_transform(beacon, &transform));
-                        if !origin_beacons.contains(&transformed) {
-                            origin_beacons.push(transformed);
-                        }
-                    }
+/// Scanners as nodes, with an edge between every pair that shares at least
+/// `threshold` beacons, labelled with their relative pose.
+struct OverlapGraph {
+    edges: Vec<Vec<(usize, Pose)>>,
+}
+
+impl OverlapGraph {
+    fn build(scanners: &[Scanner], threshold: usize, jobs: usize) -> Self {
+        let min_shared = threshold * (threshold - 1) / 2;
+        let mut edges: Vec<Vec<(usize, Pose)>> = vec![vec![]; scanners.len()];
+        for i in 0..scanners.len() {
+            for j in i + 1..scanners.len() {
+                if scanners[i].shared_distances(&scanners[j]) < min_shared {
+                    continue;
                 }
-                None => {
-                    new_scanners.push(beacons);
+                let beacon_set = &scanners[i].beacon_set;
+                if let Some(pose) = find_offset(beacon_set, &scanners[j].beacons, threshold, jobs) {
+                    edges[i].push((j, pose));
+                    edges[j].push((i, pose.inverse()));
                 }
             }
         }
-        scanners = new_scanners;
+        OverlapGraph { edges }
     }
 
-    println!("part1: {}", origin_beacons.len());
+    // poses of every scanner reachable from `start`, relative to `start`.
+    fn bfs(&self, start: usize) -> Vec<Option<Pose>> {
+        let mut poses: Vec<Option<Pose>> = vec![None; self.edges.len()];
+        poses[start] = Some(Pose::ORIGIN);
+        let mut queue = VecDeque::from([start]);
+        while let Some(i) = queue.pop_front() {
+            let pose = poses[i].unwrap();
+            for (j, relative) in &self.edges[i] {
+                if poses[*j].is_none() {
+                    poses[*j] = Some(pose.compose(relative));
+                    queue.push_back(*j);
+                }
+            }
+        }
+        poses
+    }
 
-    let mut p2 = 0;
-    for a in &scanner_positions {
-        for b in &scanner_positions {
-            let d = dist(&abs(&sub(a, b)));
-            if d > p2 {
-                p2 = d;
+    fn groups(&self) -> Vec<Vec<usize>> {
+        let mut seen = vec![false; self.edges.len()];
+        let mut groups = vec![];
+        for start in 0..self.edges.len() {
+            if seen[start] {
+                continue;
+            }
+            let group: Vec<usize> = self
+                .bfs(start)
+                .iter()
+                .enumerate()
+                .filter(|(_, pose)| pose.is_some())
+                .map(|(i, _)| i)
+                .collect();
+            for &i in &group {
+                seen[i] = true;
             }
+            groups.push(group);
         }
+        groups
+    }
+}
+
+// poses of every scanner relative to scanner 0.
+fn align(scanners: &[Scanner], threshold: usize, jobs: usize) -> Result<Vec<Pose>, AlignError> {
+    if threshold < 3 {
+        return Err(AlignError::ThresholdTooLow(threshold));
     }
+    let graph = OverlapGraph::build(scanners, threshold, jobs);
+    let poses: Option<Vec<Pose>> = graph.bfs(0).into_iter().collect();
+    poses.ok_or_else(|| AlignError::Disconnected(graph.groups()))
+}
 
-    println!("part2: {}", p2);
+/// The `--overlap N` option, defaulting to `OVERLAP`.
+fn overlap_from_args(args: &[String]) -> Result<usize, String> {
+    match args.iter().position(|arg| arg == "--overlap") {
+        Some(i) => number(args.get(i + 1).ok_or("--overlap needs a beacon count")?),
+        None => Ok(OVERLAP),
+    }
 }
 
-#[test]
-fn test_apply_transform() {
-    let start = [4, 5, 6];
+fn main() {
+    // cargo run --bin 19 -- [--overlap N] for scanners that share fewer
+    // beacons than the puzzle's 12.
+    let args: Vec<String> = env::args().skip(1).collect();
+    let jobs = parallel::jobs_from_args(&args).unwrap();
+    let overlap = overlap_from_args(&args).unwrap();
+    let runner = Runner::from_args(19);
+    let scanners = get_input();
+    let poses = runner.shared("align scanners", || {
+        align(&scanners, overlap, jobs).unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            process::exit(1);
+        })
+    });
+    runner.part(1, || {
+        let mut beacons: HashSet<Vec3> = HashSet::new();
+        for (scanner, pose) in scanners.iter().zip(&poses) {
+            beacons.extend(scanner.beacons.iter().map(|beacon| pose.apply(beacon)));
+        }
+        beacons.len()
+    });
 
-    assert_eq!(apply_transform(&start, &[1, 2, 3]), [4, 5, 6]);
-    assert_eq!(apply_transform(&start, &[3, 2, 1]), [6, 5, 4]);
-    assert_eq!(apply_transform(&start, &[3, -2, -1]), [6, -5, -4]);
+    runner.part(2, || {
+        let mut p2 = 0;
+        for a in &poses {
+            for b in &poses {
+                let d = dist(&abs(&sub(&a.position, &b.position)));
+                if d > p2 {
+                    p2 = d;
+                }
+            }
+        }
+        p2
+    });
+
+    for (i, pose) in poses.iter().enumerate() {
+        runner.note(
+            &format!("scanner {}", i),
+            format!(
+                "position {:?}, rotation {:?}",
+                pose.position, pose.rotation.0
+            ),
+        );
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use aoc_2021::rng::Rng;
+
+    #[test]
+    fn test_rotation() {
+        let start = [4, 5, 6];
+        let rotations = Rotation::all();
+        assert_eq!(rotations.len(), 24);
+        assert_eq!(Rotation::IDENTITY.apply(&start), [4, 5, 6]);
+
+        let swap = Rotation([[0, 0, 1], [0, -1, 0], [1, 0, 0]]);
+        assert_eq!(swap.apply(&start), [6, -5, 4]);
+
+        for a in &rotations {
+            assert_eq!(a.compose(&a.inverse()), Rotation::IDENTITY);
+            for b in &rotations {
+                assert_eq!(a.compose(b).apply(&start), a.apply(&b.apply(&start)));
+            }
+        }
+    }
+
+    #[test]
+    fn test_disconnected() {
+        let mut rng = Rng::new(19);
+        let mut random_beacons = |count: usize| -> Vec<Vec3> {
+            (0..count)
+                .map(|_| {
+                    let mut beacon = [0; 3];
+                    for value in beacon.iter_mut() {
+                        *value = rng.below(1000) as i32;
+                    }
+                    beacon
+                })
+                .collect()
+        };
+        let shared = random_beacons(12);
+        let pose = Pose {
+            position: [40, -1200, 30],
+            rotation: Rotation::all()[7],
+        }
+        .inverse();
+
+        let mut first = shared.clone();
+        first.extend(random_beacons(10));
+        let mut second: Vec<Vec3> = shared.iter().map(|b| pose.apply(b)).collect();
+        second.extend(random_beacons(10));
+        let scanners = vec![
+            Scanner::new(first),
+            Scanner::new(random_beacons(20)),
+            Scanner::new(second),
+        ];
+
+        let poses = OverlapGraph::build(&scanners, 12, 1).bfs(0);
+        assert_eq!(poses[2], Some(pose.inverse()));
+        assert_eq!(
+            align(&scanners, 12, 4),
+            Err(AlignError::Disconnected(vec![vec![0, 2], vec![1]]))
+        );
+        assert_eq!(
+            align(&scanners, 13, 1),
+            Err(AlignError::Disconnected(vec![vec![0], vec![1], vec![2]]))
+        );
+        assert_eq!(align(&scanners, 2, 1), Err(AlignError::ThresholdTooLow(2)));
+
+        let args = |s: &str| -> Vec<String> { s.split_whitespace().map(String::from).collect() };
+        assert_eq!(overlap_from_args(&args("--json")), Ok(OVERLAP));
+        assert_eq!(overlap_from_args(&args("--overlap 6 --jobs 2")), Ok(6));
+        assert!(overlap_from_args(&args("--overlap")).is_err());
+    }
 }
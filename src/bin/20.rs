@@ -1,120 +1,109 @@
-use std::collections::HashMap;
-use std::fs;
+use aoc_2021::automaton::{Automaton, BitGrid, Boundary, Rule};
+use aoc_2021::export;
+use aoc_2021::helpers;
+use aoc_2021::render::{ColorMap, Glyph, Renderer};
+use aoc_2021::runner::Runner;
+use std::env;
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
-enum Pixel {
-    Dark,
-    Light,
+fn get_input() -> Automaton {
+    let input = helpers::read_input(20);
+    let sections = helpers::sections(&input);
+    let [lookup, image] = sections[..] else {
+        panic!("expected a lookup table and an image separated by a blank line");
+    };
+    let table = lookup.parse_line(parse_table).unwrap();
+    let mut width = None;
+    let rows = image
+        .parse_lines(|line| {
+            let row = pixels(line)?;
+            let expected = *width.get_or_insert(row.len());
+            if row.len() != expected {
+                return Err(format!("expected {} pixels", expected));
+            }
+            Ok(row)
+        })
+        .unwrap();
+    let mut image = BitGrid::new(width.unwrap_or(0), rows.len(), false);
+    for (y, row) in rows.iter().enumerate() {
+        for (x, &lit) in row.iter().enumerate() {
+            image.set((x, y), lit);
+        }
+    }
+    Automaton::new(image, Rule::from_table(table), Boundary::Infinite)
 }
 
-struct Grid {
-    void: Pixel,
-    data: HashMap<(i32, i32), Pixel>,
-    dim: (i32, i32),
-    lookup: Vec<Pixel>,
+fn pixels(line: &str) -> Result<Vec<bool>, String> {
+    line.chars()
+        .map(|c| match c {
+            '#' => Ok(true),
+            '.' => Ok(false),
+            c => Err(format!("unexpected char '{}'", c)),
+        })
+        .collect()
 }
 
-impl Grid {
-    fn read_pixel(&self, pos: (i32, i32)) -> Pixel {
-        match self.data.get(&pos) {
-            Some(p) => *p,
-            None => self.void,
-        }
-    }
+fn parse_table(line: &str) -> Result<[bool; 512], String> {
+    let cells = pixels(line)?;
+    let len = cells.len();
+    cells
+        .try_into()
+        .map_err(|_| format!("expected 512 entries, found {}", len))
+}
 
-    fn read_pixel_section(&self, pos: (i32, i32)) -> Vec<Pixel> {
-        let (x, y) = pos;
-        vec![
-            self.read_pixel((x - 1, y - 1)),
-            self.read_pixel((x, y - 1)),
-            self.read_pixel((x + 1, y - 1)),
-            self.read_pixel((x - 1, y)),
-            self.read_pixel((x, y)),
-            self.read_pixel((x + 1, y)),
-            self.read_pixel((x - 1, y + 1)),
-            self.read_pixel((x, y + 1)),
-            self.read_pixel((x + 1, y + 1)),
-        ]
-    }
+fn main() {
+    let runner = Runner::from_args(20);
+    runner.part(1, || solve(2).count_alive().unwrap());
+    runner.part(2, || solve(50).count_alive().unwrap());
 
-    fn read_pixel_section_as_int(&self, pos: (i32, i32)) -> usize {
-        self.read_pixel_section(pos)
-            .iter()
-            .map(|p| match p {
-                Pixel::Light => 1,
-                Pixel::Dark => 0,
-            })
-            .fold(0, |acc, i| (acc << 1) | i)
+    // cargo run --bin 20 -- --export <generation> <path.pbm|ppm|svg>, where
+    // --pbm is the old name for --export.
+    let args: Vec<String> = env::args().skip(1).collect();
+    if let Some(i) = args
+        .iter()
+        .position(|arg| arg == "--export" || arg == "--pbm")
+    {
+        let gens = args.get(i + 1).expect("--export needs a generation");
+        let path = args.get(i + 2).expect("--export needs a path");
+        let image = solve(gens.parse().unwrap());
+        let color = |_, lit| if lit { export::BLACK } else { export::WHITE };
+        export::write(path, &image, color, None).unwrap();
     }
 
-    fn count_light(&self) -> usize {
-        self.data.values().filter(|p| **p == Pixel::Light).count()
+    // cargo run --bin 20 -- --visualize [--delay <ms>]
+    if let Some(renderer) = Renderer::from_args(&args).unwrap() {
+        visualize(&renderer, 50);
     }
 }
 
-fn get_input() -> Grid {
-    let input = fs::read_to_string("inputs/20.txt").unwrap();
-    let mut lines = input.lines();
-    let lookup = lines.next().unwrap();
-    let lookup = lookup
-        .chars()
-        .map(|c| match c {
-            '.' => Pixel::Dark,
-            '#' => Pixel::Light,
-            _ => panic!("unexpected char: {}", c),
-        })
-        .collect();
-    lines.next();
-    let mut grid = HashMap::new();
-    let mut m_x = 0i32;
-    let mut m_y = 0i32;
-    for (y, line) in lines.enumerate() {
-        if y as i32 > m_y {
-            m_y = y as i32;
-        }
-        for (x, c) in line.chars().enumerate() {
-            if x as i32 > m_x {
-                m_x = x as i32;
-            }
-            match c {
-                '.' => {
-                    grid.insert((x as i32, y as i32), Pixel::Dark);
-                }
-                '#' => {
-                    grid.insert((x as i32, y as i32), Pixel::Light);
-                }
-                _ => panic!("unexpected char: {}", c),
+// animates the image growing through each enhancement.
+fn visualize(renderer: &Renderer, gens: usize) {
+    let mut automaton = get_input();
+    loop {
+        let image = automaton.cells();
+        let frame = renderer.draw(image, ColorMap::Gray, |_, lit| {
+            if lit {
+                Glyph::colored('#', 1.0)
+            } else {
+                Glyph::colored('.', 0.3)
             }
+        });
+        let title = format!(
+            "enhanced {} times, {} lit",
+            automaton.generation(),
+            image
+                .count_alive()
+                .map_or("infinitely many".to_string(), |n| n.to_string())
+        );
+        renderer.show(&title, &frame);
+        if automaton.generation() == gens {
+            break;
         }
+        automaton.step();
     }
-    Grid {
-        data: grid,
-        lookup: lookup,
-        void: Pixel::Dark,
-        dim: (m_x, m_y),
-    }
-}
-
-fn main() {
-    println!("part1: {}", solve(2));
-    println!("part2: {}", solve(50));
 }
 
-fn solve(gens: i32) -> usize {
-    let mut grid = get_input();
-    let (width, height) = grid.dim;
-
-    for i in 1..=gens {
-        let mut new_data = HashMap::new();
-        for y in -(i + 1)..(height + i + 1) {
-            for x in -(i + 1)..(width + i + 1) {
-                let new_value = grid.lookup[grid.read_pixel_section_as_int((x, y))];
-                new_data.insert((x, y), new_value);
-            }
-        }
-        grid.void = grid.lookup[grid.read_pixel_section_as_int((-100000, -100000))];
-        grid.data = new_data;
-    }
-
-    grid.count_light()
+fn solve(gens: usize) -> BitGrid {
+    let mut automaton = get_input();
+    automaton.run(gens);
+    automaton.cells().clone()
 }
@@ -1,125 +1,289 @@
-use std::collections::HashMap;
+use aoc_2021::runner::Runner;
+
+#[derive(Debug, Clone, Copy)]
+struct Rules {
+    board_size: u32,
+    die_faces: u32,
+    rolls_per_turn: u32,
+    target_score: u32,
+}
+
+impl Rules {
+    fn advance(&self, space: u32, roll: u32) -> u32 {
+        ((space - 1) + roll) % self.board_size + 1
+    }
+
+    // ways each total can come up in one turn, indexed by the total.
+    // Three rolls of a three sided die gives 1, 3, 6, 7, 6, 3, 1 from 3 to 9.
+    fn roll_distribution(&self) -> Vec<u128> {
+        let mut ways = vec![1];
+        for _ in 0..self.rolls_per_turn {
+            let mut next = vec![0; ways.len() + self.die_faces as usize];
+            for (total, count) in ways.iter().enumerate() {
+                for face in 1..=self.die_faces as usize {
+                    next[total + face] += count;
+                }
+            }
+            ways = next;
+        }
+        ways
+    }
+
+    // outcomes of a single turn, used to turn universe counts into probabilities.
+    fn outcomes_per_turn(&self) -> f64 {
+        (self.die_faces as f64).powi(self.rolls_per_turn as i32)
+    }
+}
 
 struct Die {
-    index: u64,
+    faces: u32,
+    index: u32,
     count: u64,
 }
 
 impl Die {
-    fn new() -> Self {
-        Self { index: 1, count: 0 }
+    fn new(faces: u32) -> Self {
+        Self {
+            faces,
+            index: 1,
+            count: 0,
+        }
     }
 
-    fn roll(&mut self) -> u64 {
-        let mut value = 0;
-        for _ in 0..3 {
-            value += self.index;
-            self.index = (self.index % 100) + 1;
-        }
-        self.count += 3;
+    fn roll(&mut self) -> u32 {
+        let value = self.index;
+        self.index = (self.index % self.faces) + 1;
+        self.count += 1;
         value
     }
 }
 
+const STARTS: [u32; 2] = [7, 8];
+
+const DIRAC_RULES: Rules = Rules {
+    board_size: 10,
+    die_faces: 3,
+    rolls_per_turn: 3,
+    target_score: 21,
+};
+
 fn main() {
-    part1();
-    part2();
+    let runner = Runner::from_args(21);
+    runner.part(1, part1);
+    runner.part(2, part2);
+    report_chances(&runner);
 }
 
-fn part1() {
-    let mut p1_score = 0;
-    let mut p2_score = 0;
-    let mut p1 = 7;
-    let mut p2 = 8;
-    let mut die = Die::new();
-    loop {
-        p1 = (((p1 - 1) + die.roll()) % 10) + 1;
-        p1_score += p1;
-        if p1_score >= 1000 {
-            break;
-        }
-
-        p2 = (((p2 - 1) + die.roll()) % 10) + 1;
-        p2_score += p2;
-        if p2_score >= 1000 {
-            break;
+// plays until someone reaches the target, returning the lowest score
+// multiplied by the number of times the die was rolled.
+fn play_deterministic(rules: &Rules, starts: &[u32]) -> u64 {
+    let mut spaces = starts.to_vec();
+    let mut scores = vec![0; spaces.len()];
+    let mut die = Die::new(rules.die_faces);
+    'game: loop {
+        for (space, score) in spaces.iter_mut().zip(scores.iter_mut()) {
+            let roll: u32 = (0..rules.rolls_per_turn).map(|_| die.roll()).sum();
+            *space = rules.advance(*space, roll);
+            *score += *space;
+            if *score >= rules.target_score {
+                break 'game;
+            }
         }
     }
-    let score = if p1_score > p2_score {
-        p2_score * die.count
-    } else {
-        p1_score * die.count
+    *scores.iter().min().unwrap() as u64 * die.count
+}
+
+fn part1() -> u64 {
+    let rules = Rules {
+        board_size: 10,
+        die_faces: 100,
+        rolls_per_turn: 3,
+        target_score: 1000,
     };
-    println!("part1: {}", score);
+    play_deterministic(&rules, &STARTS)
 }
 
-#[derive(Copy, Clone, Hash, Eq, PartialEq)]
-struct PlayerState {
-    space: u8,
-    score: u8,
-    count: u8,
-    acc: u8,
+/// How a single player's universes progress on their own: `finished[t]` is
+/// the number reaching the target on turn `t` and `unfinished[t]` the number
+/// still short of it after turn `t`.
+struct Progress {
+    finished: Vec<u128>,
+    unfinished: Vec<u128>,
 }
 
-impl PlayerState {
-    fn new(pos: u8) -> Self {
-        Self {
-            space: pos,
-            score: 0,
-            count: 0,
-            acc: 0,
+fn player_progress(rules: &Rules, start: u32) -> Progress {
+    let distribution = rules.roll_distribution();
+    let target = rules.target_score as usize;
+    // ways[space - 1][score] for every score still below the target.
+    let mut ways = vec![vec![0u128; target]; rules.board_size as usize];
+    ways[start as usize - 1][0] = 1;
+    let mut progress = Progress {
+        finished: vec![0],
+        unfinished: vec![1],
+    };
+    while *progress.unfinished.last().unwrap() > 0 {
+        let mut next = vec![vec![0u128; target]; rules.board_size as usize];
+        let mut finished = 0;
+        for (space, scores) in ways.iter().enumerate() {
+            for (score, &count) in scores.iter().enumerate() {
+                if count == 0 {
+                    continue;
+                }
+                for (roll, &roll_ways) in distribution.iter().enumerate() {
+                    if roll_ways == 0 {
+                        continue;
+                    }
+                    let new_space = rules.advance(space as u32 + 1, roll as u32);
+                    let new_score = score + new_space as usize;
+                    if new_score >= target {
+                        finished += count * roll_ways;
+                    } else {
+                        next[new_space as usize - 1][new_score] += count * roll_ways;
+                    }
+                }
+            }
         }
+        ways = next;
+        progress.finished.push(finished);
+        progress.unfinished.push(ways.iter().flatten().sum());
     }
+    progress
+}
 
-    fn roll(&self, roll: u8) -> Self {
-        Self {
-            space: self.space,
-            score: self.score,
-            count: self.count + 1,
-            acc: self.acc + roll,
+/// Universes in which each player wins, indexed by player then turn.
+///
+/// Players' rolls are independent, so the universes in which player `i` wins on
+/// turn `t` are the ones where they finish on `t` while every earlier player
+/// is still short after `t` turns and every later one after `t - 1`.
+fn quantum_wins(rules: &Rules, starts: &[u32]) -> Vec<Vec<u128>> {
+    let progress: Vec<Progress> = starts
+        .iter()
+        .map(|start| player_progress(rules, *start))
+        .collect();
+    let unfinished = |j: usize, turn: usize| *progress[j].unfinished.get(turn).unwrap_or(&0);
+    let mut wins = vec![];
+    for (i, player) in progress.iter().enumerate() {
+        let mut player_wins = vec![0; player.finished.len()];
+        for (turn, &finished) in player.finished.iter().enumerate().skip(1) {
+            let mut count = finished;
+            for j in 0..starts.len() {
+                if j < i {
+                    count *= unfinished(j, turn);
+                } else if j > i {
+                    count *= unfinished(j, turn - 1);
+                }
+            }
+            player_wins[turn] = count;
         }
+        wins.push(player_wins);
     }
+    wins
+}
 
-    fn turn(&self) -> Self {
-        let new_space = (((self.space - 1) + self.acc) % 10) + 1;
-        let new_score = self.score + new_space;
-        Self {
-            space: new_space,
-            score: new_score,
-            count: 0,
-            acc: 0,
-        }
+fn part2() -> u128 {
+    let wins = quantum_wins(&DIRAC_RULES, &STARTS);
+    let totals: Vec<u128> = wins.iter().map(|turns| turns.iter().sum()).collect();
+    *totals.iter().max().unwrap()
+}
+
+// the chance of each player winning on each turn with the Dirac die.
+fn report_chances(runner: &Runner) {
+    let rules = DIRAC_RULES;
+    let starts = STARTS;
+    let wins = quantum_wins(&rules, &starts);
+
+    // each winning universe on turn t had every player roll for t turns, bar
+    // those after the winner who only rolled t - 1.
+    let outcomes = rules.outcomes_per_turn();
+    let turns = wins.iter().map(Vec::len).max().unwrap();
+    for turn in 1..turns {
+        let probabilities: Vec<String> = wins
+            .iter()
+            .enumerate()
+            .map(|(i, player_wins)| {
+                let count = *player_wins.get(turn).unwrap_or(&0) as f64;
+                let rolled = turn * (i + 1) + (turn - 1) * (starts.len() - i - 1);
+                let probability = count / outcomes.powi(rolled as i32);
+                format!("player {} {:.4}%", i + 1, probability * 100.0)
+            })
+            .collect();
+        runner.note(&format!("turn {}", turn), probabilities.join(", "));
     }
 }
 
-fn play(
-    cache: &mut HashMap<(PlayerState, PlayerState), (u64, u64)>,
-    p1: PlayerState,
-    p2: PlayerState,
-) -> (u64, u64) {
-    if let Some(result) = cache.get(&(p1, p2)) {
-        return *result;
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_example() {
+        let rules = Rules {
+            board_size: 10,
+            die_faces: 100,
+            rolls_per_turn: 3,
+            target_score: 1000,
+        };
+        assert_eq!(play_deterministic(&rules, &[4, 8]), 739785);
+
+        let rules = Rules {
+            die_faces: 3,
+            target_score: 21,
+            ..rules
+        };
+        assert_eq!(rules.roll_distribution()[3..], [1, 3, 6, 7, 6, 3, 1]);
+        let wins = quantum_wins(&rules, &[4, 8]);
+        let totals: Vec<u128> = wins.iter().map(|turns| turns.iter().sum()).collect();
+        assert_eq!(totals, [444356092776315, 341960390180808]);
     }
-    let result;
-    if p2.score >= 21 {
-        result = (0, 1)
-    } else if p1.count == 3 {
-        let (a, b) = play(cache, p2, p1.turn());
-        result = (b, a)
-    } else {
-        let (a1, b1) = play(cache, p1.roll(1), p2);
-        let (a2, b2) = play(cache, p1.roll(2), p2);
-        let (a3, b3) = play(cache, p1.roll(3), p2);
-        result = (a1 + a2 + a3, b1 + b2 + b3)
+
+    // plays out every universe one turn at a time, adding `universes` to the
+    // winner's count.
+    fn count_wins(
+        rules: &Rules,
+        spaces: &mut [u32],
+        scores: &mut [u32],
+        turn: usize,
+        universes: u128,
+        wins: &mut [u128],
+    ) {
+        let player = turn % spaces.len();
+        for (roll, &ways) in rules.roll_distribution().iter().enumerate() {
+            if ways == 0 {
+                continue;
+            }
+            let (space, score) = (spaces[player], scores[player]);
+            spaces[player] = rules.advance(space, roll as u32);
+            scores[player] += spaces[player];
+            if scores[player] >= rules.target_score {
+                wins[player] += universes * ways;
+            } else {
+                count_wins(rules, spaces, scores, turn + 1, universes * ways, wins);
+            }
+            spaces[player] = space;
+            scores[player] = score;
+        }
     }
-    cache.insert((p1, p2), result);
-    result
-}
 
-fn part2() {
-    let p1 = PlayerState::new(7);
-    let p2 = PlayerState::new(8);
-    let mut cache = HashMap::new();
-    let (p1_wins, p2_wins) = play(&mut cache, p1, p2);
-    println!("part2: {}", std::cmp::max(p1_wins, p2_wins));
+    #[test]
+    fn test_three_players() {
+        let rules = Rules {
+            board_size: 10,
+            die_faces: 3,
+            rolls_per_turn: 3,
+            target_score: 10,
+        };
+        let starts = [4, 8, 1];
+        let mut expected = [0; 3];
+        count_wins(
+            &rules,
+            &mut starts.clone(),
+            &mut [0; 3],
+            0,
+            1,
+            &mut expected,
+        );
+        let wins = quantum_wins(&rules, &starts);
+        let totals: Vec<u128> = wins.iter().map(|turns| turns.iter().sum()).collect();
+        assert_eq!(totals, expected);
+    }
 }
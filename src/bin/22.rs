@@ -1,233 +1,425 @@
-use std::collections::{HashMap, HashSet};
-use std::fs;
+use aoc_2021::grid::Grid;
+use aoc_2021::helpers::{self, list, number, range, split_pair};
+use aoc_2021::render::{ColorMap, Glyph, Renderer};
+use aoc_2021::runner::Runner;
+use std::env;
 use std::str::FromStr;
 
-#[derive(Debug)]
-struct Mapping {
-    x_to_compressed: HashMap<i64, i64>,
-    compressed_to_x: HashMap<i64, i64>,
-    y_to_compressed: HashMap<i64, i64>,
-    compressed_to_y: HashMap<i64, i64>,
-    z_to_compressed: HashMap<i64, i64>,
-    compressed_to_z: HashMap<i64, i64>,
-}
-
-impl Mapping {
-    fn new(x_values: &[i64], y_values: &[i64], z_values: &[i64]) -> Self {
-        let mut x_values = x_values.to_vec();
-        x_values.sort();
-        x_values.dedup();
-
-        let mut x_to_compressed = HashMap::new();
-        let mut compressed_to_x = HashMap::new();
-        for (i, v) in x_values.iter().enumerate() {
-            x_to_compressed.insert(*v, i as i64);
-            compressed_to_x.insert(i as i64, *v);
-        }
-
-        let mut y_values = y_values.to_vec();
-        y_values.sort();
-        y_values.dedup();
+#[derive(Debug, Clone, Copy)]
+enum Op {
+    On,
+    Off,
+}
 
-        let mut y_to_compressed = HashMap::new();
-        let mut compressed_to_y = HashMap::new();
-        for (i, v) in y_values.iter().enumerate() {
-            y_to_compressed.insert(*v, i as i64);
-            compressed_to_y.insert(i as i64, *v);
-        }
+/// An axis-aligned box in `D` dimensions, with inclusive ranges per axis.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Boxn<const D: usize> {
+    ranges: [(i64, i64); D],
+}
 
-        let mut z_values = z_values.to_vec();
-        z_values.sort();
-        z_values.dedup();
+type Cuboid = Boxn<3>;
 
-        let mut z_to_compressed = HashMap::new();
-        let mut compressed_to_z = HashMap::new();
-        for (i, v) in z_values.iter().enumerate() {
-            z_to_compressed.insert(*v, i as i64);
-            compressed_to_z.insert(i as i64, *v);
-        }
+fn range_intersect(a: (i64, i64), b: (i64, i64)) -> Option<(i64, i64)> {
+    let start = a.0.max(b.0);
+    let end = a.1.min(b.1);
+    if start <= end {
+        Some((start, end))
+    } else {
+        None
+    }
+}
 
-        Mapping {
-            x_to_compressed,
-            compressed_to_x,
-            y_to_compressed,
-            compressed_to_y,
-            z_to_compressed,
-            compressed_to_z,
-        }
+impl<const D: usize> Boxn<D> {
+    fn volume(&self) -> i64 {
+        self.ranges
+            .iter()
+            .map(|(start, end)| end - start + 1)
+            .product()
     }
 
-    fn compress(&self, cuboid: &Cuboid) -> Cuboid {
-        let x = (
-            self.x_to_compressed[&cuboid.x.0],
-            self.x_to_compressed[&(cuboid.x.1 + 1)],
-        );
-        let y = (
-            self.y_to_compressed[&cuboid.y.0],
-            self.y_to_compressed[&(cuboid.y.1 + 1)],
-        );
-        let z = (
-            self.z_to_compressed[&cuboid.z.0],
-            self.z_to_compressed[&(cuboid.z.1 + 1)],
-        );
-        Cuboid { x, y, z }
-    }
-
-    fn uncompressed_point_size(&self, point: &(i64, i64, i64)) -> i64 {
-        let (x, y, z) = point;
-        let x_start = self.compressed_to_x.get(&x).unwrap();
-        let x_diff = match self.compressed_to_x.get(&(x + 1)) {
-            Some(x_end) => (x_end - x_start).abs(),
-            None => 1,
-        };
+    fn contains(&self, point: [i64; D]) -> bool {
+        self.ranges
+            .iter()
+            .zip(point)
+            .all(|(&(start, end), v)| v >= start && v <= end)
+    }
 
-        let y_start = self.compressed_to_y.get(&y).unwrap();
-        let y_diff = match self.compressed_to_y.get(&(y + 1)) {
-            Some(y_end) => (y_end - y_start).abs(),
-            None => 1,
-        };
+    fn intersect(&self, other: &Boxn<D>) -> Option<Boxn<D>> {
+        let mut ranges = self.ranges;
+        for (range, other) in ranges.iter_mut().zip(other.ranges) {
+            *range = range_intersect(*range, other)?;
+        }
+        Some(Boxn { ranges })
+    }
 
-        let z_start = self.compressed_to_z.get(&z).unwrap();
-        let z_diff = match self.compressed_to_z.get(&(z + 1)) {
-            Some(z_end) => (z_end  - z_start).abs(),
-            None => 1,
+    // up to 2 * D disjoint boxes covering self but not other, peeled off
+    // one axis at a time.
+    fn subtract(&self, other: &Boxn<D>) -> Vec<Boxn<D>> {
+        let overlap = match self.intersect(other) {
+            Some(overlap) => overlap,
+            None => return vec![*self],
         };
-
-        x_diff * y_diff * z_diff
+        let mut pieces = vec![];
+        let mut rest = *self;
+        for (axis, &(overlap_start, overlap_end)) in overlap.ranges.iter().enumerate() {
+            let (start, end) = rest.ranges[axis];
+            if start < overlap_start {
+                let mut piece = rest;
+                piece.ranges[axis] = (start, overlap_start - 1);
+                pieces.push(piece);
+            }
+            if end > overlap_end {
+                let mut piece = rest;
+                piece.ranges[axis] = (overlap_end + 1, end);
+                pieces.push(piece);
+            }
+            rest.ranges[axis] = (overlap_start, overlap_end);
+        }
+        pieces
     }
 }
 
-#[derive(Debug)]
-enum Op {
-    On,
-    Off,
+/// Parses `x=a..b,y=c..d,...`, one range per axis. Axis names aren't checked.
+impl<const D: usize> FromStr for Boxn<D> {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let axes = list(s, ",", range::<i64>)?;
+        if axes.len() != D {
+            return Err(format!("expected {} ranges in: {}", D, s));
+        }
+        let mut ranges = [(0, 0); D];
+        for (bounds, (name, (start, end))) in ranges.iter_mut().zip(axes) {
+            if start > end {
+                return Err(format!("empty range: {}={}..{}", name, start, end));
+            }
+            *bounds = (start, end);
+        }
+        Ok(Boxn { ranges })
+    }
 }
 
-#[derive(Debug)]
-struct Cuboid {
-    x: (i64, i64),
-    y: (i64, i64),
-    z: (i64, i64),
+/// The reactor's lit cells as a list of disjoint boxes.
+#[derive(Default)]
+struct Reactor<const D: usize> {
+    cuboids: Vec<Boxn<D>>,
 }
 
-impl FromStr for Cuboid {
-    type Err = String;
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut parts = s.split(',');
-        let mut coords = vec![];
-        while let Some(pos) = parts.next() {
-            let mut range_parts = pos[2..].split("..");
-            let start = range_parts.next().unwrap();
-            let end = range_parts.next().unwrap();
-            coords.push((start.parse().unwrap(), end.parse().unwrap()));
+impl<const D: usize> Reactor<D> {
+    fn apply(&mut self, command: &RebootCommand<D>) {
+        let mut cuboids = vec![];
+        for cuboid in &self.cuboids {
+            cuboids.extend(cuboid.subtract(&command.cuboid));
+        }
+        if let Op::On = command.op {
+            cuboids.push(command.cuboid);
         }
-        assert_eq!(coords.len(), 3);
-        Ok(Cuboid {
-            x: coords[0],
-            y: coords[1],
-            z: coords[2],
-        })
+        self.cuboids = cuboids;
+    }
+
+    fn count_on(&self) -> i64 {
+        self.cuboids.iter().map(Boxn::volume).sum()
+    }
+
+    fn count_on_in(&self, region: &Boxn<D>) -> i64 {
+        self.cuboids
+            .iter()
+            .filter_map(|cuboid| cuboid.intersect(region))
+            .map(|cuboid| cuboid.volume())
+            .sum()
+    }
+
+    fn is_on(&self, point: [i64; D]) -> bool {
+        self.cuboids.iter().any(|cuboid| cuboid.contains(point))
     }
 }
 
 #[derive(Debug)]
-struct RebootCommand {
+struct RebootCommand<const D: usize> {
     op: Op,
-    cuboid: Cuboid,
+    cuboid: Boxn<D>,
 }
 
-impl FromStr for RebootCommand {
+impl<const D: usize> FromStr for RebootCommand<D> {
     type Err = String;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut parts = s.split(' ');
-        let op = match parts.next().unwrap() {
+        let (op, cuboid) = split_pair(s, " ")?;
+        let op = match op {
             "on" => Op::On,
             "off" => Op::Off,
-            _ => panic!("unexpected op"),
+            _ => return Err(format!("unexpected op: {}", op)),
         };
-        let cuboid = Cuboid::from_str(parts.next().unwrap())?;
+        let cuboid = cuboid.parse()?;
         Ok(RebootCommand { op, cuboid })
     }
 }
 
-fn get_input() -> Vec<RebootCommand> {
-    let input = fs::read_to_string("inputs/22.txt").unwrap();
-    input
-        .lines()
-        .map(|line| RebootCommand::from_str(line))
-        .collect::<Result<Vec<RebootCommand>, String>>()
-        .unwrap()
+fn get_input() -> Vec<RebootCommand<3>> {
+    let input = helpers::read_input(22);
+    helpers::lines(&input, RebootCommand::from_str).unwrap()
 }
 
 fn main() {
-    part1();
-    part2();
-}
-
-fn solve(mut commands: Vec<RebootCommand>) -> i64 {
-    let mut x_range = vec![];
-    let mut y_range = vec![];
-    let mut z_range = vec![];
-    for command in commands.iter() {
-        x_range.push(command.cuboid.x.0);
-        x_range.push(command.cuboid.x.1 + 1);
-
-        y_range.push(command.cuboid.y.0);
-        y_range.push(command.cuboid.y.1 + 1);
-
-        z_range.push(command.cuboid.z.0);
-        z_range.push(command.cuboid.z.1 + 1);
-    }
-    let mapping = Mapping::new(&x_range, &y_range, &z_range);
-
-    for command in &mut commands {
-        command.cuboid = mapping.compress(&command.cuboid);
-    }
-
-    let mut world = HashSet::new();
-    for command in &commands {
-        for x in command.cuboid.x.0..command.cuboid.x.1 {
-            for y in command.cuboid.y.0..command.cuboid.y.1 {
-                for z in command.cuboid.z.0..command.cuboid.z.1 {
-                    match command.op {
-                        Op::On => {
-                            world.insert((x, y, z));
-                        }
-                        Op::Off => {
-                            world.remove(&(x, y, z));
-                        }
-                    }
-                }
+    let runner = Runner::from_args(22);
+    runner.part(1, part1);
+    runner.part(2, part2);
+
+    // cargo run --bin 22 -- <x>,<y>,<z> to check whether a cube ends up on.
+    let args: Vec<String> = env::args().skip(1).collect();
+    if let Some(point) = args.first().filter(|arg| !arg.starts_with("--")) {
+        let coords: Vec<i64> = list(point, ",", number).unwrap();
+        let reactor = reboot(&get_input());
+        let on = reactor.is_on(coords.try_into().unwrap());
+        println!("{}: {}", point, if on { "on" } else { "off" });
+    }
+
+    // cargo run --bin 22 -- --visualize [--delay <ms>]
+    if let Some(renderer) = Renderer::from_args(&args).unwrap() {
+        visualize(&renderer);
+    }
+}
+
+// animates the initialization procedure seen from above, each column of the
+// -50..50 region shaded by how many of its cubes are on.
+fn visualize(renderer: &Renderer) {
+    const RAMP: &[u8] = b" .:-=+*#%@";
+    let region = Cuboid {
+        ranges: [(-50, 50); 3],
+    };
+    let (start, end) = region.ranges[0];
+    let size = (end - start + 1) as usize;
+    let mut reactor = Reactor::default();
+    for (i, command) in get_input().iter().enumerate() {
+        // clipped to the region, as part 1 counts them.
+        let Some(cuboid) = command.cuboid.intersect(&region) else {
+            continue;
+        };
+        reactor.apply(&RebootCommand {
+            op: command.op,
+            cuboid,
+        });
+        let mut counts = vec![];
+        for y in start..=end {
+            for x in start..=end {
+                let column = Cuboid {
+                    ranges: [(x, x), (y, y), (start, end)],
+                };
+                counts.push(reactor.count_on_in(&column));
             }
         }
+        let grid = Grid::new(size, size, counts);
+        let frame = renderer.draw(&grid, ColorMap::Heat, |_, count| {
+            if count == 0 {
+                return Glyph::plain(' ');
+            }
+            let level = count as f64 / size as f64;
+            let ch = RAMP[1 + (level * (RAMP.len() - 2) as f64).round() as usize];
+            Glyph::colored(char::from(ch), level)
+        });
+        let title = format!("command {}: {} cubes on", i + 1, reactor.count_on());
+        renderer.show(&title, &frame);
     }
+}
 
-    let mut result = 0;
-    for point in &world {
-        result += mapping.uncompressed_point_size(point);
+fn reboot<const D: usize>(commands: &[RebootCommand<D>]) -> Reactor<D> {
+    let mut reactor = Reactor::default();
+    for command in commands {
+        reactor.apply(command);
     }
-    result
+    reactor
 }
 
-fn part1() {
+fn part1() -> i64 {
     let commands = get_input();
-    let commands = commands
-        .into_iter()
-        .filter(|command| {
-            command.cuboid.x.0 >= -50
-                && command.cuboid.x.1 <= 50
-                && command.cuboid.y.0 >= -50
-                && command.cuboid.y.1 <= 50
-                && command.cuboid.z.0 >= -50
-                && command.cuboid.z.1 <= 50
-        })
-        .collect::<Vec<RebootCommand>>();
-
-    println!("part1: {}", solve(commands));
-}
-
-fn part2() {
+    let region = Cuboid {
+        ranges: [(-50, 50); 3],
+    };
+    reboot(&commands).count_on_in(&region)
+}
+
+fn part2() -> i64 {
     let commands = get_input();
 
-    println!("part2: {}", solve(commands));
+    reboot(&commands).count_on()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use aoc_2021::rng::Rng;
+    use std::collections::HashSet;
+
+    /// Maps the boundaries seen along one axis to consecutive indices.
+    /// Compressed cell `i` stands for the half-open range
+    /// `values[i]..values[i + 1]`.
+    struct CoordinateCompressor {
+        values: Vec<i64>,
+    }
+
+    impl CoordinateCompressor {
+        fn new(mut values: Vec<i64>) -> Self {
+            values.sort_unstable();
+            values.dedup();
+            CoordinateCompressor { values }
+        }
+
+        fn compress(&self, value: i64) -> usize {
+            self.values.binary_search(&value).unwrap()
+        }
+
+        fn width(&self, index: usize) -> i64 {
+            self.values[index + 1] - self.values[index]
+        }
+    }
+
+    // calls f on every cell in the half-open ranges, varying the first axis fastest.
+    fn for_each_cell<const D: usize>(ranges: [(usize, usize); D], mut f: impl FnMut([usize; D])) {
+        if ranges.iter().any(|(start, end)| start >= end) {
+            return;
+        }
+        let mut cell = ranges.map(|(start, _)| start);
+        loop {
+            f(cell);
+            let mut axis = 0;
+            loop {
+                if axis == D {
+                    return;
+                }
+                cell[axis] += 1;
+                if cell[axis] < ranges[axis].1 {
+                    break;
+                }
+                cell[axis] = ranges[axis].0;
+                axis += 1;
+            }
+        }
+    }
+
+    // counts lit cells by coordinate compression, visiting every compressed cell.
+    // Far slower than `reboot`, but a simple check on it.
+    fn solve_compressed<const D: usize>(commands: &[RebootCommand<D>]) -> i64 {
+        let compressors: [CoordinateCompressor; D] = std::array::from_fn(|axis| {
+            let mut values = vec![];
+            for command in commands {
+                let (start, end) = command.cuboid.ranges[axis];
+                values.push(start);
+                values.push(end + 1);
+            }
+            CoordinateCompressor::new(values)
+        });
+
+        let mut world = HashSet::new();
+        for command in commands {
+            let mut ranges = [(0, 0); D];
+            for (axis, range) in ranges.iter_mut().enumerate() {
+                let (start, end) = command.cuboid.ranges[axis];
+                let compressor = &compressors[axis];
+                *range = (compressor.compress(start), compressor.compress(end + 1));
+            }
+            for_each_cell(ranges, |cell| match command.op {
+                Op::On => {
+                    world.insert(cell);
+                }
+                Op::Off => {
+                    world.remove(&cell);
+                }
+            });
+        }
+
+        world
+            .iter()
+            .map(|cell| {
+                cell.iter()
+                    .zip(&compressors)
+                    .map(|(&index, compressor)| compressor.width(index))
+                    .product::<i64>()
+            })
+            .sum()
+    }
+
+    fn random_commands<const D: usize>(seed: u64, count: usize) -> Vec<RebootCommand<D>> {
+        let mut rng = Rng::new(seed);
+        let mut random = |n: i64| rng.below(n as u64) as i64;
+        let mut commands = vec![];
+        for _ in 0..count {
+            let mut ranges = [(0, 0); D];
+            for range in ranges.iter_mut() {
+                let start = random(30) - 15;
+                *range = (start, start + random(12));
+            }
+            let op = if random(3) == 0 { Op::Off } else { Op::On };
+            commands.push(RebootCommand {
+                op,
+                cuboid: Boxn { ranges },
+            });
+        }
+        commands
+    }
+
+    #[test]
+    fn test_matches_compressed() {
+        let example = [
+            "on x=10..12,y=10..12,z=10..12",
+            "on x=11..13,y=11..13,z=11..13",
+            "off x=9..11,y=9..11,z=9..11",
+            "on x=10..10,y=10..10,z=10..10",
+        ];
+        let commands: Vec<RebootCommand<3>> =
+            example.iter().map(|line| line.parse().unwrap()).collect();
+        let reactor = reboot(&commands);
+        assert_eq!(reactor.count_on(), 39);
+        assert!(reactor.is_on([10, 10, 10]));
+        assert!(!reactor.is_on([11, 11, 11]));
+        assert_eq!(solve_compressed(&commands), 39);
+
+        let commands = random_commands::<3>(22, 40);
+        let region = Cuboid {
+            ranges: [(-5, 5), (0, 8), (-20, 0)],
+        };
+        let reactor = reboot(&commands);
+        let mut in_region = 0;
+        for x in -5..=5 {
+            for y in 0..=8 {
+                for z in -20..=0 {
+                    in_region += reactor.is_on([x, y, z]) as i64;
+                }
+            }
+        }
+        assert_eq!(reactor.count_on_in(&region), in_region);
+        assert_eq!(reactor.count_on(), solve_compressed(&commands));
+
+        // part 1 only counts inside the region, including the parts of
+        // commands that reach outside it.
+        let clipped: Vec<RebootCommand<3>> = commands
+            .iter()
+            .filter_map(|command| {
+                let cuboid = command.cuboid.intersect(&region)?;
+                Some(RebootCommand {
+                    op: command.op,
+                    cuboid,
+                })
+            })
+            .collect();
+        assert!(commands.iter().any(|command| {
+            matches!(command.cuboid.intersect(&region), Some(cuboid) if cuboid != command.cuboid)
+        }));
+        assert_eq!(solve_compressed(&clipped), in_region);
+    }
+
+    #[test]
+    fn test_other_dimensions() {
+        let commands: Vec<RebootCommand<2>> = ["on x=0..3,y=0..3", "off x=1..2,y=1..5"]
+            .iter()
+            .map(|line| line.parse().unwrap())
+            .collect();
+        assert_eq!(reboot(&commands).count_on(), 10);
+        assert!("on x=0..3".parse::<RebootCommand<2>>().is_err());
+        assert!("on x=0..3,y=0..1,z=0..1"
+            .parse::<RebootCommand<2>>()
+            .is_err());
+
+        let commands = random_commands::<2>(2, 40);
+        assert_eq!(reboot(&commands).count_on(), solve_compressed(&commands));
+        let commands = random_commands::<4>(4, 25);
+        assert_eq!(reboot(&commands).count_on(), solve_compressed(&commands));
+    }
 }
@@ -1,338 +1,527 @@
-use std::cmp::Ordering;
-use std::collections::{HashSet, BinaryHeap, HashMap};
+use aoc_2021::helpers;
+use aoc_2021::render::Renderer;
+use aoc_2021::runner::Runner;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::env;
+use std::str::FromStr;
+use std::time::Duration;
 
-#[derive(Debug, Hash, Eq, PartialEq, Clone, Copy)]
-enum Amphipods {
-    Amber,
-    Bronze,
-    Copper,
-    Desert,
-}
+// rows folded out of the diagram for part 2.
+const UNFOLDED: [&str; 2] = ["  #D#C#B#A#", "  #D#B#A#C#"];
 
-impl Amphipods {
-    const VALUES: [Self; 4] = [Self::Amber, Self::Bronze, Self::Copper, Self::Desert];
+/// An amphipod type, `A` being 0. Each type costs ten times more per step
+/// than the one before and belongs in the room with the same index.
+#[derive(Debug, Hash, Eq, PartialEq, Clone, Copy)]
+struct Amphipod(u8);
 
-    fn from_char(c: char) -> Self {
-        match c {
-            'A' => Amphipods::Amber,
-            'B' => Amphipods::Bronze,
-            'C' => Amphipods::Copper,
-            'D' => Amphipods::Desert,
-            _ => panic!("unexpected char found"),
+impl Amphipod {
+    fn from_char(c: char) -> Option<Self> {
+        if c.is_ascii_uppercase() {
+            Some(Amphipod(c as u8 - b'A'))
+        } else {
+            None
         }
     }
 
     fn cost(&self) -> u64 {
-        match self {
-            Amphipods::Amber => 1,
-            Amphipods::Bronze => 10,
-            Amphipods::Copper => 100,
-            Amphipods::Desert => 1000,
-        }
+        10u64.pow(self.0 as u32)
     }
-}
 
-#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
-enum Node {
-    Hallway(Option<Amphipods>),
-    HallwayMoveOnly,
-    Home(Amphipods, Option<Amphipods>),
+    fn to_char(self) -> char {
+        (b'A' + self.0) as char
+    }
 }
 
-#[derive(Debug, Clone, Eq, PartialEq, Hash)]
-struct Graph {
+/// Every hallway cell followed by every room slot, packed `bits` to a cell.
+/// A cell holds zero when empty and the amphipod type plus one otherwise.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+struct State(u128);
+
+/// An amphipod walking from one cell to another in a single move.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Move {
+    from: usize,
+    to: usize,
     cost: u64,
-    nodes: Vec<Node>,
-    edges: Vec<Vec<usize>>,
 }
 
-impl Ord for Graph {
-    fn cmp(&self, other: &Self) -> Ordering {
-        other.cost.cmp(&self.cost)
-    }
+/// Hallway cells that have to be empty to walk between two places, and the
+/// number of steps it takes.
+#[derive(Debug, Clone, Copy)]
+struct Path {
+    mask: u64,
+    steps: u64,
 }
 
-impl PartialOrd for Graph {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
+impl Path {
+    // from hallway cell `from` to just below hallway cell `to`, `from` excluded.
+    fn new(from: usize, to: usize) -> Self {
+        let (low, high) = if from < to {
+            (from + 1, to)
+        } else {
+            (to, from - 1)
+        };
+        let mask = (low..=high).fold(0, |mask, i| mask | 1 << i);
+        Path {
+            mask,
+            steps: from.abs_diff(to) as u64 + 1,
+        }
     }
 }
 
-impl Graph {
-    fn new(positions: &[char]) -> Self {
-        let mut nodes = vec![];
-        let mut edges = vec![];
-        let room_x = [2, 4, 6, 8];
-        for i in 0..11 {
-            let node = if room_x.contains(&i) {
-                Node::HallwayMoveOnly
-            } else {
-                Node::Hallway(None)
-            };
-            nodes.push(node);
-            let edge = if i == 0 {
-                vec![1]
-            } else if i == 10 {
-                vec![9]
-            } else {
-                vec![i - 1, i + 1]
-            };
-            edges.push(edge);
-        }
-        for (i, a) in Amphipods::VALUES.iter().enumerate() {
-            let node_top = Node::Home(*a, None);
-            let node_top_i = nodes.len();
-            nodes.push(node_top);
-
-            let node_bot = Node::Home(*a, None);
-            let node_bot_i = nodes.len();
-            nodes.push(node_bot);
-
-            let hallway_i = 2 + (i * 2);
-            edges[hallway_i].push(node_top_i);
-            edges.push(vec![hallway_i, node_bot_i]);
-            edges.push(vec![node_top_i]);
+/// The layout of a hallway with equally deep rooms hanging off it, along with
+/// the walks between them. Positions change through `State`s instead.
+#[derive(Debug)]
+struct Burrow {
+    hallway_len: usize,
+    entrances: Vec<usize>,
+    depth: usize,
+    bits: usize,
+    // hallway cells an amphipod may stop on, which is all but the entrances.
+    stops: Vec<usize>,
+    // to_room[cell][room]: from a hallway cell to the room's top slot.
+    to_room: Vec<Vec<Path>>,
+    // between[from][to]: from one room's top slot to another's.
+    between: Vec<Vec<Path>>,
+    start: State,
+    goal: State,
+}
+
+impl Burrow {
+    fn new(hallway_len: usize, entrances: &[usize], depth: usize) -> Result<Self, String> {
+        let bits = (usize::BITS - entrances.len().leading_zeros()) as usize;
+        if hallway_len > 64 || (hallway_len + entrances.len() * depth) * bits > 128 {
+            return Err("burrow too large to pack into a state".to_string());
         }
-        let mut graph = Graph {
-            cost: 0,
-            nodes,
-            edges,
+        let stops = (0..hallway_len)
+            .filter(|i| !entrances.contains(i))
+            .collect();
+        let to_room = (0..hallway_len)
+            .map(|i| entrances.iter().map(|&e| Path::new(i, e)).collect())
+            .collect();
+        let between = entrances
+            .iter()
+            .map(|&from| {
+                entrances
+                    .iter()
+                    .map(|&to| {
+                        let path = Path::new(from, to);
+                        Path {
+                            mask: path.mask | 1 << from,
+                            steps: path.steps + 1,
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+        let mut burrow = Burrow {
+            hallway_len,
+            entrances: entrances.to_vec(),
+            depth,
+            bits,
+            stops,
+            to_room,
+            between,
+            start: State(0),
+            goal: State(0),
         };
-        graph.set_positions(positions);
-        graph
+        for room in 0..entrances.len() {
+            for d in 0..depth {
+                let slot = burrow.slot(room, d);
+                burrow.goal = burrow.set(burrow.goal, slot, Some(Amphipod(room as u8)));
+            }
+        }
+        Ok(burrow)
     }
 
-    fn set_positions(&mut self, positions: &[char]) {
-        assert!(positions.len() == 8);
-        let (a1, a5) = self.get_homes(&Amphipods::Amber);
-        let (a2, a6) = self.get_homes(&Amphipods::Bronze);
-        let (a3, a7) = self.get_homes(&Amphipods::Copper);
-        let (a4, a8) = self.get_homes(&Amphipods::Desert);
-        let mapping = [a1, a2, a3, a4, a5, a6, a7, a8];
-        for (i, c) in positions.iter().enumerate() {
-            self.place(mapping[i], &Amphipods::from_char(*c));
-        }
+    fn rooms(&self) -> usize {
+        self.entrances.len()
     }
 
-    fn is_complete(&self) -> bool {
-        for node in &self.nodes {
-            if let Node::Home(a, b) = node {
-                match b {
-                    None => return false,
-                    Some(b) => {
-                        if a != b {
-                            return false;
-                        }
-                    }
-                }
-            }
-        }
-        true
+    fn slot(&self, room: usize, d: usize) -> usize {
+        self.hallway_len + room * self.depth + d
     }
 
-    fn get_homes(&self, a: &Amphipods) -> (usize, usize) {
-        let mut top = 0;
-        let mut bot = 0;
-        for (i, node) in self.nodes.iter().enumerate() {
-            if let Node::Home(b, _) = node {
-                if a == b {
-                    if self.edges[i].len() == 1 {
-                        bot = i;
-                    } else {
-                        top = i;
-                    }
-                }
-            }
+    fn get(&self, state: State, cell: usize) -> Option<Amphipod> {
+        let value = (state.0 >> (cell * self.bits)) & ((1 << self.bits) - 1);
+        if value == 0 {
+            None
+        } else {
+            Some(Amphipod(value as u8 - 1))
         }
-        assert!(top != 0);
-        assert!(bot != 0);
-        (top, bot)
     }
 
-    fn is_home_open(&self, home: usize) -> bool {
-        match self.nodes[home] {
-            Node::Home(_, None) => true,
-            _ => false,
-        }
+    fn set(&self, state: State, cell: usize, a: Option<Amphipod>) -> State {
+        let shift = cell * self.bits;
+        let value = a.map_or(0, |a| a.0 as u128 + 1);
+        State(state.0 & !(((1 << self.bits) - 1) << shift) | value << shift)
     }
 
-    fn are_homes_available(&self, a: &Amphipods) -> bool {
-        let (top_home, bot_home) = self.get_homes(a);
-        if !self.is_home_open(top_home) {
-            let b = self.get_type(top_home).unwrap();
-            if a != b {
-                return false;
-            }
-        }
-        if !self.is_home_open(bot_home) {
-            let b = self.get_type(bot_home).unwrap();
-            if a != b {
-                return false;
+    fn hallway_mask(&self, state: State) -> u64 {
+        (0..self.hallway_len)
+            .filter(|&i| self.get(state, i).is_some())
+            .fold(0, |mask, i| mask | 1 << i)
+    }
+
+    // the top amphipod in a room along with its slot.
+    fn room_top(&self, state: State, room: usize) -> Option<(usize, Amphipod)> {
+        (0..self.depth).find_map(|d| self.get(state, self.slot(room, d)).map(|a| (d, a)))
+    }
+
+    // the deepest empty slot of a room, if it only holds amphipods that belong there.
+    fn open_slot(&self, state: State, room: usize) -> Option<usize> {
+        let mut open = None;
+        for d in 0..self.depth {
+            match self.get(state, self.slot(room, d)) {
+                None => open = Some(d),
+                Some(a) if a.0 as usize == room => (),
+                Some(_) => return None,
             }
         }
-        true
+        open
     }
 
-    fn get_type(&self, i: usize) -> Option<&Amphipods> {
-        match &self.nodes[i] {
-            Node::Hallway(Some(a)) => Some(a),
-            Node::Home(_, Some(a)) => Some(a),
-            _ => None,
-        }
+    // amphipods at the bottom of a room that never need to move again.
+    fn settled(&self, state: State, room: usize) -> usize {
+        (0..self.depth)
+            .rev()
+            .take_while(|&d| self.get(state, self.slot(room, d)) == Some(Amphipod(room as u8)))
+            .count()
+    }
+
+    fn apply(&self, state: State, m: &Move) -> State {
+        let a = self.get(state, m.from);
+        self.set(self.set(state, m.from, None), m.to, a)
     }
 
-    fn clear(&mut self, i: usize) {
-        match &self.nodes[i] {
-            Node::Hallway(Some(_)) => {
-                self.nodes[i] = Node::Hallway(None);
+    fn moves(&self, state: State) -> Vec<Move> {
+        let mut results = vec![];
+        let hallway = self.hallway_mask(state);
+        for i in 0..self.hallway_len {
+            let a = match self.get(state, i) {
+                Some(a) => a,
+                None => continue,
+            };
+            let room = a.0 as usize;
+            let path = &self.to_room[i][room];
+            if let Some(d) = self.open_slot(state, room) {
+                if hallway & path.mask == 0 {
+                    results.push(Move {
+                        from: i,
+                        to: self.slot(room, d),
+                        cost: (path.steps + d as u64) * a.cost(),
+                    });
+                }
             }
-            Node::Home(t, Some(_)) => {
-                self.nodes[i] = Node::Home(*t, None);
+        }
+        for room in 0..self.rooms() {
+            let (d, a) = match self.room_top(state, room) {
+                Some(top) => top,
+                None => continue,
+            };
+            if self.settled(state, room) == self.depth - d {
+                continue;
+            }
+            let from = self.slot(room, d);
+            let home = a.0 as usize;
+            if home != room {
+                if let Some(home_d) = self.open_slot(state, home) {
+                    let path = &self.between[room][home];
+                    if hallway & path.mask == 0 {
+                        let steps = d as u64 + path.steps + home_d as u64;
+                        results.push(Move {
+                            from,
+                            to: self.slot(home, home_d),
+                            cost: steps * a.cost(),
+                        });
+                    }
+                }
+            }
+            for &i in &self.stops {
+                let path = &self.to_room[i][room];
+                if hallway & (path.mask | 1 << i) == 0 {
+                    results.push(Move {
+                        from,
+                        to: i,
+                        cost: (path.steps + d as u64) * a.cost(),
+                    });
+                }
             }
-            _ => panic!("can't clear"),
         }
+        results
     }
 
-    fn place(&mut self, i: usize, a: &Amphipods) {
-        match &self.nodes[i] {
-            Node::Hallway(None) => {
-                self.nodes[i] = Node::Hallway(Some(*a));
+    /// A lower bound on the remaining cost: every amphipod walks straight to
+    /// its room's top slot, stepping aside if it has to leave its own room,
+    /// and then down as far as the amphipods arriving before it let it.
+    fn estimate(&self, state: State) -> u64 {
+        let mut total = 0;
+        for i in 0..self.hallway_len {
+            if let Some(a) = self.get(state, i) {
+                total += self.to_room[i][a.0 as usize].steps * a.cost();
             }
-            Node::Home(t, None) => {
-                self.nodes[i] = Node::Home(*t, Some(*a));
+        }
+        for room in 0..self.rooms() {
+            let settled = self.settled(state, room);
+            for d in 0..self.depth - settled {
+                if let Some(a) = self.get(state, self.slot(room, d)) {
+                    let steps = if a.0 as usize == room {
+                        d as u64 + 4
+                    } else {
+                        d as u64 + self.between[room][a.0 as usize].steps
+                    };
+                    total += steps * a.cost();
+                }
             }
-            _ => panic!("can't place"),
+            let arriving = (self.depth - settled) as u64;
+            total += arriving * arriving.saturating_sub(1) / 2 * Amphipod(room as u8).cost();
         }
+        total
     }
 
-    fn try_move(&self, from: usize, to: usize) -> Option<Self> {
-        let from_type = self.get_type(from).unwrap();
-        let mut visited = HashSet::new();
-        let mut stack = vec![];
-        stack.push((from, 0));
-        while let Some((current, cost)) = stack.pop() {
-            if visited.contains(&current) {
-                continue;
+    /// A* over packed states, keeping the cheapest known cost of each along
+    /// with the move that reached it. Returns the cheapest sequence of moves.
+    fn solve(&self) -> Option<Vec<Move>> {
+        let mut best: HashMap<State, (u64, Option<Move>)> = HashMap::new();
+        let mut heap = BinaryHeap::new();
+        best.insert(self.start, (0, None));
+        heap.push(Reverse((self.estimate(self.start), 0, self.start)));
+        while let Some(Reverse((_, cost, state))) = heap.pop() {
+            if state == self.goal {
+                return Some(self.trace(&best, state));
             }
-            visited.insert(current);
-            if current == to {
-                let mut new_graph = self.clone();
-                new_graph.clear(from);
-                new_graph.place(to, from_type);
-                new_graph.cost += cost;
-                // println!("moved from {:?} to {:?}", &self.nodes[from], &self.nodes[to]);
-                return Some(new_graph);
+            if cost > best[&state].0 {
+                continue;
             }
-            for i in &self.edges[current] {
-                match &self.nodes[*i] {
-                    Node::Hallway(None) | Node::HallwayMoveOnly | Node::Home(_, None) => {
-                        stack.push((*i, cost + from_type.cost()));
-                    }
-                    _ => (),
+            for m in self.moves(state) {
+                let next = self.apply(state, &m);
+                let next_cost = cost + m.cost;
+                if best.get(&next).is_none_or(|&(known, _)| next_cost < known) {
+                    best.insert(next, (next_cost, Some(m)));
+                    heap.push(Reverse((next_cost + self.estimate(next), next_cost, next)));
                 }
             }
         }
         None
     }
 
-    fn get_open_hallways(&self) -> Vec<usize> {
-        let mut result = vec![];
-        for (i, node) in self.nodes.iter().enumerate() {
-            if let Node::Hallway(None) = node {
-                result.push(i);
-            }
+    // walks the recorded moves back from `state` to the start.
+    fn trace(&self, best: &HashMap<State, (u64, Option<Move>)>, mut state: State) -> Vec<Move> {
+        let mut moves = vec![];
+        while let Some(m) = best[&state].1 {
+            let a = self.get(state, m.to);
+            state = self.set(self.set(state, m.to, None), m.from, a);
+            moves.push(m);
         }
-        result
+        moves.reverse();
+        moves
     }
 
-    fn next_states(&self) -> Vec<Self> {
-        let mut results = vec![];
-        for (i, node) in self.nodes.iter().enumerate() {
-            match node {
-                Node::Hallway(Some(a)) => {
-                    if self.are_homes_available(a) {
-                        let (top_home, bot_home) = self.get_homes(a);
-                        if let Some(next) = self.try_move(i, bot_home) {
-                            results.push(next);
-                            continue;
-                        }
-                        if let Some(next) = self.try_move(i, top_home) {
-                            results.push(next);
-                            continue;
-                        }
-                    }
-                }
-                Node::Home(h, Some(a)) => {
-                    if h != a {
-                        if self.are_homes_available(a) {
-                            let (top_home, bot_home) = self.get_homes(a);
-                            if let Some(next) = self.try_move(i, bot_home) {
-                                results.push(next);
-                                continue;
-                            }
-                            if let Some(next) = self.try_move(i, top_home) {
-                                results.push(next);
-                                continue;
-                            }
-                        }
-                        for j in self.get_open_hallways() {
-                            if let Some(next) = self.try_move(i, j) {
-                                results.push(next);
-                            }
-                        }
-                    } else {
-                        let (_, bot_home) = self.get_homes(a);
-                        if let Some(bot_type) = self.get_type(bot_home) {
-                            if bot_type != a {
-                                for j in self.get_open_hallways() {
-                                    if let Some(next) = self.try_move(i, j) {
-                                        results.push(next);
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-                _ => (),
-            }
+    fn describe(&self, cell: usize) -> String {
+        if cell < self.hallway_len {
+            format!("hallway {}", cell)
+        } else {
+            let room = (cell - self.hallway_len) / self.depth;
+            let d = (cell - self.hallway_len) % self.depth;
+            format!("room {} slot {}", Amphipod(room as u8).to_char(), d)
         }
-        results
+    }
+
+    /// Draws the burrow in the same form as the puzzle input.
+    fn render(&self, state: State) -> String {
+        let width = self.hallway_len + 2;
+        let cell = |cell: usize| self.get(state, cell).map_or('.', Amphipod::to_char);
+        let mut lines = vec!["#".repeat(width)];
+        let hallway: String = (0..self.hallway_len).map(cell).collect();
+        lines.push(format!("#{}#", hallway));
+        let first = self.entrances.iter().min().unwrap() + 1;
+        let last = self.entrances.iter().max().unwrap() + 1;
+        for d in 0..=self.depth {
+            let line: String = (0..width)
+                .map(|x| match self.entrances.iter().position(|&e| e + 1 == x) {
+                    Some(room) if d < self.depth => cell(self.slot(room, d)),
+                    _ if d == 0 || (x + 1 >= first && x <= last + 1) => '#',
+                    _ => ' ',
+                })
+                .collect();
+            lines.push(line.trim_end().to_string());
+        }
+        lines.join("\n")
     }
 }
 
-fn main() {
-    let initial_positions = ['D', 'A', 'C', 'D',
-                             'C', 'A', 'B', 'B'];
-    let graph = Graph::new(&initial_positions);
-    let mut heap = BinaryHeap::new();
-    heap.push(graph);
-    let mut lowest_cost = u64::MAX;
-    let mut counter = 0;
-    while let Some(graph) = heap.pop() {
-        if graph.is_complete() {
-            lowest_cost = graph.cost;
-            break;
+/// Parses the puzzle diagram. The hallway is the second line and every line
+/// after it with open cells is a row of rooms, the leftmost room being `A`'s.
+impl FromStr for Burrow {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lines: Vec<Vec<char>> = s.lines().map(|line| line.chars().collect()).collect();
+        let hallway = lines.get(1).ok_or("missing hallway")?;
+        if hallway.len() < 3 || hallway[0] != '#' || hallway[hallway.len() - 1] != '#' {
+            return Err("hallway must be walled at both ends".to_string());
         }
-        for next_graph in graph.next_states() {
-            heap.push(next_graph);
+        let hallway = &hallway[1..hallway.len() - 1];
+        let is_open = |c: &char| *c != '#' && *c != ' ';
+        let rows: Vec<&Vec<char>> = lines[2..]
+            .iter()
+            .take_while(|line| line.iter().any(is_open))
+            .collect();
+        let columns: Vec<usize> = match rows.first() {
+            Some(row) => (0..row.len()).filter(|&x| is_open(&row[x])).collect(),
+            None => return Err("missing rooms".to_string()),
+        };
+        if columns.iter().any(|&x| x == 0 || x > hallway.len()) {
+            return Err("room outside of the hallway".to_string());
         }
-        counter += 1;
-        if counter >= 10_000 {
-            let mut compact = HashMap::new();
-            for mut graph in heap.drain() {
-                let cost = graph.cost;
-                graph.cost = 0;
-                let value = compact.entry(graph).or_insert(u64::MAX);
-                if cost < *value {
-                    *value = cost;
-                }
+        if columns.len() > 26 {
+            return Err("too many rooms".to_string());
+        }
+        for (y, row) in rows.iter().enumerate() {
+            let open: Vec<usize> = (0..row.len()).filter(|&x| is_open(&row[x])).collect();
+            if open != columns {
+                return Err(format!("room row {} doesn't line up with the first", y + 1));
+            }
+        }
+
+        let entrances: Vec<usize> = columns.iter().map(|x| x - 1).collect();
+        let mut burrow = Burrow::new(hallway.len(), &entrances, rows.len())?;
+        let mut counts = vec![0; columns.len()];
+        let mut occupy = |burrow: &mut Burrow, cell: usize, c: char| -> Result<(), String> {
+            if c == '.' {
+                return Ok(());
             }
-            for (mut graph, cost) in compact {
-                graph.cost = cost;
-                heap.push(graph);
+            let a = Amphipod::from_char(c)
+                .filter(|a| (a.0 as usize) < counts.len())
+                .ok_or(format!("unexpected char '{}'", c))?;
+            counts[a.0 as usize] += 1;
+            burrow.start = burrow.set(burrow.start, cell, Some(a));
+            Ok(())
+        };
+        for (i, &c) in hallway.iter().enumerate() {
+            if c != '.' && entrances.contains(&i) {
+                return Err(format!("amphipod '{}' is blocking a room", c));
             }
-            counter = 0;
+            occupy(&mut burrow, i, c)?;
         }
+        for (y, row) in rows.iter().enumerate() {
+            for (room, &x) in columns.iter().enumerate() {
+                let slot = burrow.slot(room, y);
+                occupy(&mut burrow, slot, row[x])?;
+            }
+        }
+        if counts.iter().any(|&count| count != rows.len()) {
+            return Err(format!("expected {} amphipods of each type", rows.len()));
+        }
+        Ok(burrow)
+    }
+}
+
+// the diagram with the extra rows from part 2 folded out under the first row of rooms.
+fn unfold(diagram: &str) -> String {
+    let mut lines: Vec<&str> = diagram.lines().collect();
+    lines.splice(3..3, UNFOLDED);
+    lines.join("\n")
+}
+
+fn total_cost(moves: &[Move]) -> u64 {
+    moves.iter().map(|m| m.cost).sum()
+}
+
+// shows the burrow after every move.
+fn replay(burrow: &Burrow, moves: &[Move], renderer: &Renderer) {
+    let mut state = burrow.start;
+    let mut total = 0;
+    renderer.show("start", &format!("{}\n", burrow.render(state)));
+    for (i, m) in moves.iter().enumerate() {
+        let a = burrow.get(state, m.from).unwrap();
+        state = burrow.apply(state, m);
+        total += m.cost;
+        let title = format!(
+            "move {}: {} from {} to {}, cost {} (total {})",
+            i + 1,
+            a.to_char(),
+            burrow.describe(m.from),
+            burrow.describe(m.to),
+            m.cost,
+            total
+        );
+        renderer.show(&title, &format!("{}\n", burrow.render(state)));
+    }
+}
+
+fn main() {
+    let runner = Runner::from_args(23);
+    let input = helpers::read_input(23);
+    let part1: Burrow = input.parse().unwrap();
+    let mut part1_moves = vec![];
+    runner.part(1, || {
+        part1_moves = part1.solve().unwrap();
+        total_cost(&part1_moves)
+    });
+
+    let part2: Burrow = unfold(&input).parse().unwrap();
+    let mut part2_moves = vec![];
+    runner.part(2, || {
+        part2_moves = part2.solve().unwrap();
+        total_cost(&part2_moves)
+    });
+
+    // cargo run --bin 23 -- --replay <part> [delay ms], animating in place
+    // on a terminal when given a delay and listing every move otherwise.
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("--replay") {
+        let renderer = match args.get(2) {
+            Some(ms) => Renderer::for_stdout(Duration::from_millis(ms.parse().unwrap())),
+            None => Renderer::new(false, Duration::ZERO),
+        };
+        match args.get(1).map(String::as_str) {
+            Some("1") => replay(&part1, &part1_moves, &renderer),
+            Some("2") => replay(&part2, &part2_moves, &renderer),
+            _ => eprintln!("usage: --replay <1|2> [delay ms]"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn solve(diagram: &str) -> Option<u64> {
+        let burrow: Burrow = diagram.parse().unwrap();
+        let moves = burrow.solve()?;
+        let end = moves
+            .iter()
+            .fold(burrow.start, |state, m| burrow.apply(state, m));
+        assert_eq!(end, burrow.goal);
+        Some(total_cost(&moves))
+    }
+
+    #[test]
+    fn test_burrows() {
+        let example = "\
+#############
+#...........#
+###B#C#B#D###
+  #A#D#C#A#
+  #########
+";
+        assert_eq!(solve(example), Some(12521));
+        let burrow: Burrow = example.parse().unwrap();
+        assert_eq!(burrow.render(burrow.start), example.trim_end());
+        assert_eq!(solve(&unfold(example)), Some(44169));
+
+        let small = "\
+###########
+#.........#
+###B#A#C###
+  #######
+";
+        assert_eq!(solve(small), Some(46));
+        assert_eq!(solve("#####\n#A..#\n###.###\n  ###"), Some(3));
+
+        assert!("#####\n#...#\n###A###\n  #.#".parse::<Burrow>().is_err());
+        assert!("#####\n#...#\n###E###\n  ###".parse::<Burrow>().is_err());
+        assert!("#####\n#.A.#\n##.##\n ###".parse::<Burrow>().is_err());
     }
-    println!("part1: {}", lowest_cost);
 }
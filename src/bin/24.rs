@@ -1,130 +1,197 @@
-use std::fs;
-use std::str::FromStr;
+use aoc_2021::alu::compile::{optimize, to_rust, Compiled};
+use aoc_2021::alu::symbolic::{block_template, execute};
+use aoc_2021::alu::{blocks, disassemble, live_in, parse_program, Inst, Reg, RegOrIm};
+use aoc_2021::helpers;
+use aoc_2021::runner::Runner;
+use std::collections::HashMap;
+use std::env;
 
-#[derive(Debug)]
-enum Reg {
-    X = 0,
-    Y = 1,
-    Z = 2,
-    W = 3,
-}
-
-impl FromStr for Reg {
-    type Err = String;
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
-            "x" => Ok(Reg::X),
-            "y" => Ok(Reg::Y),
-            "z" => Ok(Reg::Z),
-            "w" => Ok(Reg::W),
-            _ => Err("Invalid reg.".to_string()),
-        }
+/// The smallest and largest model numbers MONAD accepts, if any.
+///
+/// Runs the blocks one digit at a time over every distinct state of the
+/// registers the next block reads, remembering the smallest and largest
+/// digits leading to each. States are dropped once `z` is too big for the
+/// remaining `div z` instructions to bring back to zero, which assumes as
+/// MONAD does that `z` never shrinks any other way.
+fn solve_monad(insts: &[Inst]) -> Result<Option<(u64, u64)>, String> {
+    let blocks = blocks(insts);
+    let mut shrink = vec![1i64; blocks.len() + 1];
+    for (i, block) in blocks.iter().enumerate().rev() {
+        let divisor = block
+            .iter()
+            .map(|inst| match inst {
+                Inst::Div(Reg::Z, RegOrIm::Im(v)) => v.abs().max(1),
+                _ => 1,
+            })
+            .fold(1i64, |a, b| a.saturating_mul(b));
+        shrink[i] = shrink[i + 1].saturating_mul(divisor);
     }
-}
 
-#[derive(Debug)]
-enum RegOrIm {
-    Reg(Reg),
-    Im(i64),
-}
-
-impl FromStr for RegOrIm {
-    type Err = String;
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if s.chars().next().unwrap().is_alphabetic() {
-            Ok(RegOrIm::Reg(Reg::from_str(s)?))
+    let mut states = HashMap::from([([0i64; 4], (0u64, 0u64))]);
+    for (i, block) in blocks.iter().enumerate() {
+        let live = blocks
+            .get(i + 1)
+            .map_or([false, false, true, false], |next| live_in(next));
+        let start = if i == 0 { [Some(0); 4] } else { [None; 4] };
+        let compiled = Compiled::new(&optimize(block, start, live));
+        let digits: Vec<i64> = if let Some(Inst::Inp(_)) = block.first() {
+            (1..=9).collect()
         } else {
-            Ok(RegOrIm::Im(s.parse().unwrap()))
+            vec![]
+        };
+        let mut next_states: HashMap<[i64; 4], (u64, u64)> = HashMap::new();
+        for (registers, (low, high)) in states {
+            let inputs: Vec<Option<i64>> = if digits.is_empty() {
+                vec![None]
+            } else {
+                digits.iter().map(|&d| Some(d)).collect()
+            };
+            for digit in inputs {
+                let input: Vec<i64> = digit.into_iter().collect();
+                let mut key = compiled.run(registers, &input)?;
+                if key[Reg::Z as usize].abs() >= shrink[i + 1] {
+                    continue;
+                }
+                for (value, live) in key.iter_mut().zip(live) {
+                    if !live {
+                        *value = 0;
+                    }
+                }
+                let (low, high) = match digit {
+                    Some(d) => (low * 10 + d as u64, high * 10 + d as u64),
+                    None => (low, high),
+                };
+                let entry = next_states.entry(key).or_insert((low, high));
+                entry.0 = entry.0.min(low);
+                entry.1 = entry.1.max(high);
+            }
         }
+        states = next_states;
     }
+    Ok(states
+        .into_iter()
+        .filter(|(registers, _)| registers[Reg::Z as usize] == 0)
+        .map(|(_, range)| range)
+        .reduce(|a, b| (a.0.min(b.0), a.1.max(b.1))))
 }
 
-#[derive(Debug)]
-enum Inst {
-    Inp(Reg),
-    Add(Reg, RegOrIm),
-    Mul(Reg, RegOrIm),
-    Div(Reg, RegOrIm),
-    Mod(Reg, RegOrIm),
-    Eql(Reg, RegOrIm),
-}
+/// Solves MONAD from the constraints on the symbolic paths that leave `z` at
+/// zero, reporting the block layout and constraints along the way.
+fn analyse(insts: &[Inst], runner: &Runner) -> Result<Option<(u64, u64)>, String> {
+    let blocks = blocks(insts);
+    if let Some(varying) = block_template(&blocks) {
+        let positions: Vec<String> = varying.iter().map(|i| (i + 1).to_string()).collect();
+        runner.note(
+            "template",
+            format!(
+                "{} blocks alike but for the immediates at instructions {}",
+                blocks.len(),
+                positions.join(", ")
+            ),
+        );
+        for (i, block) in blocks.iter().enumerate() {
+            let immediates: Vec<String> = varying
+                .iter()
+                .map(|&j| match block[j].source() {
+                    Some(RegOrIm::Im(v)) => v.to_string(),
+                    _ => "-".to_string(),
+                })
+                .collect();
+            runner.note(&format!("block {}", i), immediates.join(" "));
+        }
+    }
 
-impl FromStr for Inst {
-    type Err = String;
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut parts = s.split(' ');
-        let inst = parts.next().unwrap();
-        let reg = Reg::from_str(parts.next().unwrap())?;
-        match inst {
-            "inp" => Ok(Inst::Inp(reg)),
-            "add" => {
-                let reg_or_im = RegOrIm::from_str(parts.next().unwrap())?;
-                Ok(Inst::Add(reg, reg_or_im))
-            }
-            "mul" => {
-                let reg_or_im = RegOrIm::from_str(parts.next().unwrap())?;
-                Ok(Inst::Mul(reg, reg_or_im))
-            }
-            "div" => {
-                let reg_or_im = RegOrIm::from_str(parts.next().unwrap())?;
-                Ok(Inst::Div(reg, reg_or_im))
-            }
-            "mod" => {
-                let reg_or_im = RegOrIm::from_str(parts.next().unwrap())?;
-                Ok(Inst::Mod(reg, reg_or_im))
-            }
-            "eql" => {
-                let reg_or_im = RegOrIm::from_str(parts.next().unwrap())?;
-                Ok(Inst::Eql(reg, reg_or_im))
-            }
-            _ => Err("Invalid inst.".to_string()),
+    let number = |digits: Vec<i64>| digits.iter().fold(0, |n, &d| n * 10 + d as u64);
+    let mut result: Option<(u64, u64)> = None;
+    for path in execute(insts)? {
+        match path.accepts() {
+            Some(true) => (),
+            Some(false) => continue,
+            None => return Err("can't tell whether a path is accepted".to_string()),
+        }
+        for constraint in &path.constraints {
+            runner.note("constraint", constraint.to_string());
+        }
+        if let Some((smallest, largest)) = path.digit_bounds()? {
+            let (smallest, largest) = (number(smallest), number(largest));
+            result = Some(match result {
+                Some((low, high)) => (low.min(smallest), high.max(largest)),
+                None => (smallest, largest),
+            });
         }
     }
+    Ok(result)
 }
 
-struct Alu {
-    registers: [i64; 4],
+fn get_input() -> Vec<Inst> {
+    let input = helpers::read_input(24);
+    parse_program(&input).unwrap()
 }
 
-impl Alu {
-    fn run_program(&mut self, insts: &[Inst]) {
-        todo!()
+fn main() {
+    let runner = Runner::from_args(24);
+    let insts = get_input();
+    let (smallest, largest) = runner.shared("solve blocks", || {
+        solve_monad(&insts)
+            .unwrap()
+            .expect("no model number accepted")
+    });
+    runner.part(1, || largest);
+    runner.part(2, || smallest);
+
+    if let Err(e) = analyse(&insts, &runner) {
+        runner.note("analysis", e);
     }
 
-    fn run_inst(&mut self, inst: &Inst) {
-        match inst {
-            Inst::Inp(r) => (),
-            Inst::Add(r, v) => (),
-            Inst::Mul(r, v) => (),
-            Inst::Div(r, v) => (),
-            Inst::Mod(r, v) => (),
-            Inst::Eql(r, v) => (),
+    // cargo run --bin 24 -- --emit-rust, or --disassemble for a listing of
+    // the program split into blocks.
+    match env::args().nth(1).as_deref() {
+        Some("--emit-rust") => {
+            let ops = optimize(&insts, [Some(0); 4], [false, false, true, false]);
+            println!("{}", to_rust(&ops, "monad"));
         }
-        todo!()
+        Some("--disassemble") => println!("{}", disassemble(&insts)),
+        _ => {}
     }
+}
 
-    fn get_reg(&self, r: Reg) -> i64 {
-        self.registers[r as usize]
+#[cfg(test)]
+mod test {
+    use super::*;
+    use aoc_2021::alu::Alu;
+
+    fn digits(number: u64) -> Vec<i64> {
+        number
+            .to_string()
+            .chars()
+            .map(|c| c.to_digit(10).unwrap() as i64)
+            .collect()
     }
 
-    fn set_reg(&mut self, r: Reg, v: i64) {
-        self.registers[r as usize] = v;
+    // runs MONAD itself on a model number, to double check the solver.
+    fn accepts(insts: &[Inst], number: u64) -> bool {
+        let mut alu = Alu::new(&digits(number));
+        alu.run_program(insts).is_ok() && alu.get_reg(Reg::Z) == 0
     }
-}
 
-fn get_input() -> Vec<Inst> {
-    let input = fs::read_to_string("inputs/24.txt").unwrap();
-    input
-        .lines()
-        .map(|line| Inst::from_str(line).unwrap())
-        .collect()
-}
+    fn parse(program: &str) -> Vec<Inst> {
+        parse_program(program).unwrap()
+    }
 
-fn main() {
-    part1();
-}
+    #[test]
+    fn test_solve() {
+        // accepts two digit numbers whose digits are equal
+        let program = parse("inp x\ninp y\neql x y\neql x 0\nadd z x");
+        assert_eq!(solve_monad(&program), Ok(Some((11, 99))));
+        assert_eq!(solve_monad(&parse("inp x\nadd z 1")), Ok(None));
+    }
 
-fn part1() {
-    let insts = get_input();
-    println!("part1: {:?}", insts);
+    #[test]
+    fn test_input() {
+        let insts = get_input();
+        let (smallest, largest) = solve_monad(&insts).unwrap().unwrap();
+        assert!(accepts(&insts, largest) && accepts(&insts, smallest));
+        let runner = Runner::new(24, false);
+        assert_eq!(analyse(&insts, &runner), Ok(Some((smallest, largest))));
+    }
 }
@@ -0,0 +1,224 @@
+use aoc_2021::grid::{Grid, GridView};
+use aoc_2021::helpers;
+use aoc_2021::render::{ColorMap, Glyph, Renderer};
+use aoc_2021::runner::Runner;
+use std::env;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Cell {
+    Empty,
+    East,
+    South,
+}
+
+impl Cell {
+    fn to_char(self) -> char {
+        match self {
+            Cell::Empty => '.',
+            Cell::East => '>',
+            Cell::South => 'v',
+        }
+    }
+}
+
+/// The sea floor, which wraps around at both edges.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct SeaFloor {
+    grid: Grid<Cell>,
+}
+
+impl SeaFloor {
+    fn parse(input: &str) -> Result<Self, String> {
+        let mut width = None;
+        let rows = helpers::lines(input, |line| {
+            let row = line
+                .chars()
+                .map(|c| match c {
+                    '.' => Ok(Cell::Empty),
+                    '>' => Ok(Cell::East),
+                    'v' => Ok(Cell::South),
+                    c => Err(format!("unexpected '{}'", c)),
+                })
+                .collect::<Result<Vec<Cell>, String>>()?;
+            let expected = *width.get_or_insert(row.len());
+            if row.len() != expected {
+                return Err(format!("expected {} cells", expected));
+            }
+            Ok(row)
+        })?;
+        let width = width.unwrap_or(0);
+        if width == 0 {
+            return Err("empty sea floor".to_string());
+        }
+        let grid = Grid::new(width, rows.len(), rows.concat());
+        Ok(SeaFloor { grid })
+    }
+
+    fn get(&self, x: isize, y: isize) -> Cell {
+        let (width, height) = (self.grid.width() as isize, self.grid.height() as isize);
+        self.grid
+            .get((x.rem_euclid(width), y.rem_euclid(height)))
+            .unwrap()
+    }
+
+    // moves every cucumber of one herd at once, each looking only at where
+    // things stood before any of them moved. Returns how many moved.
+    fn move_herd(&mut self, herd: Cell) -> usize {
+        let (dx, dy) = if herd == Cell::East { (1, 0) } else { (0, 1) };
+        let (width, height) = (self.grid.width(), self.grid.height());
+        let mut moved = 0;
+        let mut data = Vec::with_capacity(width * height);
+        for y in 0..height as isize {
+            for x in 0..width as isize {
+                let cell = match self.get(x, y) {
+                    cell if cell == herd && self.get(x + dx, y + dy) == Cell::Empty => {
+                        moved += 1;
+                        Cell::Empty
+                    }
+                    Cell::Empty if self.get(x - dx, y - dy) == herd => herd,
+                    cell => cell,
+                };
+                data.push(cell);
+            }
+        }
+        self.grid = Grid::new(width, height, data);
+        moved
+    }
+
+    /// The east-facing herd moves, then the south-facing one. Returns whether
+    /// anything moved.
+    fn step(&mut self) -> bool {
+        let east = self.move_herd(Cell::East);
+        let south = self.move_herd(Cell::South);
+        east + south > 0
+    }
+}
+
+/// The first step on which no sea cucumber moves, calling `on_step` with the
+/// step number and the sea floor after each step.
+fn settle(mut floor: SeaFloor, mut on_step: impl FnMut(usize, &SeaFloor)) -> usize {
+    let mut steps = 1;
+    while floor.step() {
+        on_step(steps, &floor);
+        steps += 1;
+    }
+    on_step(steps, &floor);
+    steps
+}
+
+// the first argument that isn't an option or an option's value.
+fn input_path(args: &[String]) -> Option<&str> {
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--jobs" | "--delay" => {
+                args.next();
+            }
+            arg if arg.starts_with("--") => (),
+            path => return Some(path),
+        }
+    }
+    None
+}
+
+fn get_input(path: Option<&str>) -> SeaFloor {
+    let input = match path {
+        Some(path) => helpers::read_file(path),
+        None => helpers::read_input(25),
+    };
+    SeaFloor::parse(&input).unwrap()
+}
+
+fn main() {
+    // cargo run --bin 25 -- [input path] [--visualize [--delay <ms>]]
+    let args: Vec<String> = env::args().skip(1).collect();
+    let path = input_path(&args);
+
+    let runner = Runner::from_args(25);
+    let floor = get_input(path);
+    runner.part(1, || settle(floor, |_, _| ()));
+
+    if let Some(renderer) = Renderer::from_args(&args).unwrap() {
+        visualize(&renderer, get_input(path));
+    }
+}
+
+// animates the herds until they settle, east-facing red and south-facing
+// yellow.
+fn visualize(renderer: &Renderer, floor: SeaFloor) {
+    settle(floor, |step, floor| {
+        let frame = renderer.draw(&floor.grid, ColorMap::Heat, |_, cell| {
+            let level = match cell {
+                Cell::Empty => None,
+                Cell::East => Some(0.4),
+                Cell::South => Some(0.7),
+            };
+            Glyph {
+                ch: cell.to_char(),
+                level,
+            }
+        });
+        renderer.show(&format!("after {} steps", step), &frame);
+    });
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    impl SeaFloor {
+        fn render(&self) -> String {
+            let mut lines = vec![];
+            for y in 0..self.grid.height() as isize {
+                let line: String = (0..self.grid.width() as isize)
+                    .map(|x| self.get(x, y).to_char())
+                    .collect();
+                lines.push(line);
+            }
+            lines.join("\n")
+        }
+    }
+
+    #[test]
+    fn test_settle() {
+        let mut floor = SeaFloor::parse("...>>>>>...").unwrap();
+        floor.step();
+        assert_eq!(floor.render(), "...>>>>.>..");
+        floor.step();
+        assert_eq!(floor.render(), "...>>>.>.>.");
+
+        // both herds wrap, and the east herd moves first.
+        let mut floor = SeaFloor::parse("..v\n>.>\n...").unwrap();
+        floor.step();
+        assert_eq!(floor.render(), "..v\n.>>\n...");
+        let mut floor = SeaFloor::parse(".v\n>.").unwrap();
+        floor.step();
+        assert_eq!(floor.render(), ".v\n.>");
+
+        let example = "v...>>.vv>\n.vv>>.vv..\n>>.>v>...v\n>>v>>.>.v.\nv>v.vv.v..\n\
+                       >.>>..v...\n.vv..>.>v.\nv.v..>>v.v\n....v..v.>";
+        let floor = SeaFloor::parse(example).unwrap();
+        let mut last = String::new();
+        assert_eq!(settle(floor, |_, floor| last = floor.render()), 58);
+        assert_eq!(
+            last,
+            "..>>v>vv..\n..v.>>vv..\n..>>v>>vv.\n..>>>>>vv.\nv......>vv\n\
+             v>v....>>v\nvvv.....>>\n>vv......>\n.>v.vv.v.."
+        );
+
+        assert!(SeaFloor::parse("..>\n.x.").is_err());
+        assert_eq!(
+            SeaFloor::parse("..>\n.."),
+            Err("line 2: expected 3 cells in: ..".to_string())
+        );
+        assert!(SeaFloor::parse("").is_err());
+
+        let args = |s: &str| -> Vec<String> { s.split_whitespace().map(String::from).collect() };
+        assert_eq!(input_path(&args("--json /tmp/25.txt")), Some("/tmp/25.txt"));
+        assert_eq!(input_path(&args("--jobs 4 --json")), None);
+        assert_eq!(
+            input_path(&args("--visualize --delay 5 in.txt")),
+            Some("in.txt")
+        );
+    }
+}
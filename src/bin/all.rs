@@ -0,0 +1,158 @@
+use aoc_2021::parallel;
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::{self, Command};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+
+// days whose input is written into the source.
+const BUILT_IN_INPUT: [u32; 2] = [17, 21];
+
+enum Status {
+    // what the day printed.
+    Ran(String),
+    // the day's input file is missing, which isn't the day's fault.
+    Skipped(String),
+    Failed(String),
+}
+
+struct Outcome {
+    day: u32,
+    status: Status,
+}
+
+fn parse_days(args: &[String]) -> Result<Vec<u32>, String> {
+    let mut days = vec![];
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == "--jobs" {
+            args.next();
+            continue;
+        }
+        if arg.starts_with("--") {
+            continue;
+        }
+        match arg.parse() {
+            Ok(day) if (1..=25).contains(&day) => days.push(day),
+            _ => return Err(format!("invalid day '{}'", arg)),
+        }
+    }
+    if days.is_empty() {
+        days = (1..=25).collect();
+    }
+    Ok(days)
+}
+
+fn run(dir: &Path, day: u32, args: &[String]) -> Outcome {
+    let input = format!("inputs/{:02}.txt", day);
+    if !BUILT_IN_INPUT.contains(&day) && !Path::new(&input).exists() {
+        let status = Status::Skipped(format!("no {}", input));
+        return Outcome { day, status };
+    }
+    let exe = dir.join(format!("{:02}", day));
+    let status = match Command::new(&exe).args(args).output() {
+        Err(e) => Status::Failed(format!(
+            "{}: {} (build every day first with cargo build --release)",
+            exe.display(),
+            e
+        )),
+        Ok(output) if output.status.success() => {
+            Status::Ran(String::from_utf8_lossy(&output.stdout).into_owned())
+        }
+        Ok(output) => Status::Failed(format!(
+            "{}\n{}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim_end()
+        )),
+    };
+    Outcome { day, status }
+}
+
+fn main() {
+    // cargo run --release --bin all -- [days...] [--json] [--jobs N]
+    let args: Vec<String> = env::args().skip(1).collect();
+    let (days, jobs) = match parse_days(&args).and_then(|days| {
+        let jobs = parallel::jobs_from_args(&args)?;
+        Ok((days, jobs))
+    }) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(2);
+        }
+    };
+    let json = args.iter().any(|arg| arg == "--json");
+    // the threads are shared out between the days running at once, so a
+    // day that splits its own work gets what's left over.
+    let workers = jobs.min(days.len());
+    let day_jobs = (jobs / workers).max(1);
+    let mut day_args = vec!["--jobs".to_string(), day_jobs.to_string()];
+    if json {
+        day_args.push("--json".to_string());
+    }
+    let dir: PathBuf = env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(Path::to_path_buf))
+        .unwrap_or_default();
+
+    // the workers take the next day in turn; results are printed in the
+    // order the days were asked for, as soon as each one's turn comes.
+    let next = AtomicUsize::new(0);
+    let (sender, receiver) = mpsc::channel();
+    let mut failed = vec![];
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            let sender = sender.clone();
+            let (next, days, dir, day_args) = (&next, &days, &dir, &day_args);
+            scope.spawn(move || loop {
+                let i = next.fetch_add(1, Ordering::Relaxed);
+                if i >= days.len() {
+                    break;
+                }
+                sender.send((i, run(dir, days[i], day_args))).unwrap();
+            });
+        }
+        drop(sender);
+
+        let mut pending: Vec<Option<Outcome>> = days.iter().map(|_| None).collect();
+        let mut printed = 0;
+        for (i, outcome) in receiver {
+            pending[i] = Some(outcome);
+            while let Some(outcome) = pending.get_mut(printed).and_then(Option::take) {
+                match outcome.status {
+                    Status::Ran(stdout) if json => print!("{}", stdout),
+                    Status::Ran(stdout) => print!("day {:02}\n{}", outcome.day, stdout),
+                    Status::Skipped(reason) => {
+                        eprintln!("day {:02} skipped: {}", outcome.day, reason)
+                    }
+                    Status::Failed(e) => {
+                        eprintln!("day {:02} failed: {}", outcome.day, e);
+                        failed.push(outcome.day);
+                    }
+                }
+                printed += 1;
+            }
+        }
+    });
+
+    if !failed.is_empty() {
+        let failed: Vec<String> = failed.iter().map(|day| format!("{:02}", day)).collect();
+        eprintln!("failed: {}", failed.join(", "));
+        process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_days() {
+        let args = |s: &str| -> Vec<String> { s.split_whitespace().map(String::from).collect() };
+        assert_eq!(parse_days(&args("")), Ok((1..=25).collect()));
+        assert_eq!(parse_days(&args("19 --jobs 4 3 --json")), Ok(vec![19, 3]));
+        assert!(parse_days(&args("26")).is_err());
+        assert!(parse_days(&args("day1")).is_err());
+    }
+}
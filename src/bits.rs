@@ -0,0 +1,517 @@
+use std::convert::TryFrom;
+use std::fmt;
+
+pub mod expr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    Sum,
+    Product,
+    Minimum,
+    Maximum,
+    GreaterThan,
+    LessThan,
+    EqualTo,
+}
+
+impl Op {
+    pub const VALUES: [Self; 7] = [
+        Op::Sum,
+        Op::Product,
+        Op::Minimum,
+        Op::Maximum,
+        Op::GreaterThan,
+        Op::LessThan,
+        Op::EqualTo,
+    ];
+
+    pub fn type_id(&self) -> u8 {
+        match self {
+            Op::Sum => 0,
+            Op::Product => 1,
+            Op::Minimum => 2,
+            Op::Maximum => 3,
+            Op::GreaterThan => 5,
+            Op::LessThan => 6,
+            Op::EqualTo => 7,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Op::Sum => "sum",
+            Op::Product => "product",
+            Op::Minimum => "min",
+            Op::Maximum => "max",
+            Op::GreaterThan => "gt",
+            Op::LessThan => "lt",
+            Op::EqualTo => "eq",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Op::VALUES.iter().find(|op| op.name() == name).copied()
+    }
+
+    pub fn is_comparison(&self) -> bool {
+        matches!(self, Op::GreaterThan | Op::LessThan | Op::EqualTo)
+    }
+
+    // combines two operands of an associative op, `None` on overflow.
+    fn combine(&self, a: u64, b: u64) -> Option<u64> {
+        match self {
+            Op::Sum => a.checked_add(b),
+            Op::Product => a.checked_mul(b),
+            Op::Minimum => Some(a.min(b)),
+            Op::Maximum => Some(a.max(b)),
+            Op::GreaterThan => Some((a > b) as u64),
+            Op::LessThan => Some((a < b) as u64),
+            Op::EqualTo => Some((a == b) as u64),
+        }
+    }
+}
+
+impl TryFrom<u8> for Op {
+    type Error = String;
+    fn try_from(type_id: u8) -> Result<Self, Self::Error> {
+        Op::VALUES
+            .iter()
+            .find(|op| op.type_id() == type_id)
+            .copied()
+            .ok_or(format!("unexpected op id: {}", type_id))
+    }
+}
+
+const LITERAL_TYPE_ID: u8 = 4;
+const TOTAL_LENGTH_BITS: usize = 15;
+const SUB_PACKET_COUNT_BITS: usize = 11;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Packet {
+    Literal(u8, u64),
+    Operator(u8, Op, Vec<Packet>),
+}
+
+pub fn hex_to_bytes(s: &str) -> Result<Vec<u8>, String> {
+    let mut nibbles = vec![];
+    for c in s.trim().chars() {
+        match c.to_digit(16) {
+            Some(v) => nibbles.push(v as u8),
+            None => return Err(format!("invalid hex char: {}", c)),
+        }
+    }
+    Ok(nibbles
+        .chunks(2)
+        .map(|pair| (pair[0] << 4) | pair.get(1).copied().unwrap_or(0))
+        .collect())
+}
+
+/// Reads big-endian bit fields out of packed bytes.
+pub struct BitReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> BitReader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, pos: 0 }
+    }
+
+    pub fn pos(&self) -> usize {
+        self.pos
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.bytes.len() * 8 - self.pos
+    }
+
+    pub fn read(&mut self, count: usize) -> Result<u64, String> {
+        if count > 64 {
+            return Err(format!("can't read {} bits at once", count));
+        }
+        if count > self.remaining() {
+            return Err(format!("unexpected end of message at bit {}", self.pos));
+        }
+        let mut result = 0;
+        for _ in 0..count {
+            let byte = self.bytes[self.pos / 8];
+            let bit = (byte >> (7 - self.pos % 8)) & 1;
+            result = (result << 1) | bit as u64;
+            self.pos += 1;
+        }
+        Ok(result)
+    }
+}
+
+/// Appends big-endian bit fields to a packed byte buffer.
+#[derive(Default)]
+pub struct BitWriter {
+    bytes: Vec<u8>,
+    len: usize,
+}
+
+impl BitWriter {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn write(&mut self, value: u64, count: usize) {
+        for i in (0..count).rev() {
+            self.push_bit((value >> i) & 1 == 1);
+        }
+    }
+
+    pub fn append(&mut self, other: &BitWriter) {
+        let mut reader = BitReader::new(&other.bytes);
+        for _ in 0..other.len {
+            self.push_bit(reader.read(1).unwrap() == 1);
+        }
+    }
+
+    fn push_bit(&mut self, bit: bool) {
+        if self.len.is_multiple_of(8) {
+            self.bytes.push(0);
+        }
+        if bit {
+            *self.bytes.last_mut().unwrap() |= 1 << (7 - self.len % 8);
+        }
+        self.len += 1;
+    }
+
+    /// Renders the buffer as hex, zero padding the final digit.
+    pub fn to_hex(&self) -> String {
+        let digits = self.len.div_ceil(4);
+        let mut result = String::with_capacity(digits);
+        for i in 0..digits {
+            let byte = self.bytes[i / 2];
+            let nibble = if i % 2 == 0 { byte >> 4 } else { byte & 0xf };
+            result.push(std::char::from_digit(nibble as u32, 16).unwrap());
+        }
+        result.to_uppercase()
+    }
+}
+
+impl Packet {
+    pub fn version(&self) -> u8 {
+        match self {
+            Packet::Literal(version, _) => *version,
+            Packet::Operator(version, _, _) => *version,
+        }
+    }
+
+    pub fn from_hex(s: &str) -> Result<Self, String> {
+        let bytes = hex_to_bytes(s)?;
+        Packet::decode(&mut BitReader::new(&bytes))
+    }
+
+    pub fn decode(reader: &mut BitReader) -> Result<Self, String> {
+        let version = reader.read(3)? as u8;
+        let type_id = reader.read(3)? as u8;
+        if type_id == LITERAL_TYPE_ID {
+            let mut value: u64 = 0;
+            loop {
+                let prefix = reader.read(1)?;
+                if value.leading_zeros() < 4 {
+                    return Err("literal value overflows u64".to_string());
+                }
+                value = (value << 4) | reader.read(4)?;
+                if prefix == 0 {
+                    break;
+                }
+            }
+            return Ok(Packet::Literal(version, value));
+        }
+
+        let op = Op::try_from(type_id)?;
+        let mut packets = vec![];
+        match reader.read(1)? {
+            0 => {
+                let total_length = reader.read(TOTAL_LENGTH_BITS)? as usize;
+                let end_pos = reader.pos() + total_length;
+                while reader.pos() < end_pos {
+                    packets.push(Packet::decode(reader)?);
+                }
+                if reader.pos() != end_pos {
+                    return Err(format!(
+                        "sub-packets overran their total length of {}",
+                        total_length
+                    ));
+                }
+            }
+            _ => {
+                let sub_packet_count = reader.read(SUB_PACKET_COUNT_BITS)?;
+                for _ in 0..sub_packet_count {
+                    packets.push(Packet::decode(reader)?);
+                }
+            }
+        }
+        Ok(Packet::Operator(version, op, packets))
+    }
+
+    pub fn to_hex(&self) -> Result<String, String> {
+        let mut writer = BitWriter::new();
+        self.encode(&mut writer)?;
+        Ok(writer.to_hex())
+    }
+
+    /// Encodes the packet, preferring the 11-bit sub-packet count over the
+    /// 15-bit total length whenever the count fits.
+    pub fn encode(&self, writer: &mut BitWriter) -> Result<(), String> {
+        if self.version() > 7 {
+            return Err(format!("version {} doesn't fit in 3 bits", self.version()));
+        }
+        writer.write(self.version() as u64, 3);
+        match self {
+            Packet::Literal(_, value) => {
+                writer.write(LITERAL_TYPE_ID as u64, 3);
+                let groups = (64 - value.leading_zeros() as usize).div_ceil(4);
+                let groups = groups.max(1);
+                for i in (0..groups).rev() {
+                    writer.write((i > 0) as u64, 1);
+                    writer.write((value >> (i * 4)) & 0xf, 4);
+                }
+            }
+            Packet::Operator(_, op, packets) => {
+                writer.write(op.type_id() as u64, 3);
+                let mut sub_packets = BitWriter::new();
+                for packet in packets {
+                    packet.encode(&mut sub_packets)?;
+                }
+                if packets.len() < 1 << SUB_PACKET_COUNT_BITS {
+                    writer.write(1, 1);
+                    writer.write(packets.len() as u64, SUB_PACKET_COUNT_BITS);
+                } else if sub_packets.len() < 1 << TOTAL_LENGTH_BITS {
+                    writer.write(0, 1);
+                    writer.write(sub_packets.len() as u64, TOTAL_LENGTH_BITS);
+                } else {
+                    return Err("too many sub-packets to encode".to_string());
+                }
+                writer.append(&sub_packets);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Packet {
+    /// Evaluates the packet with checked arithmetic, reporting overflow and
+    /// malformed operators instead of panicking or wrapping.
+    pub fn eval(&self) -> Result<u64, String> {
+        match self {
+            Packet::Literal(_, value) => Ok(*value),
+            Packet::Operator(_, op, packets) => {
+                let values = packets
+                    .iter()
+                    .map(Packet::eval)
+                    .collect::<Result<Vec<u64>, String>>()?;
+                if op.is_comparison() && values.len() != 2 {
+                    return Err(format!(
+                        "{} expects 2 sub-packets, found {}",
+                        op.name(),
+                        values.len()
+                    ));
+                }
+                let mut values = values.into_iter();
+                let first = match (op, values.next()) {
+                    (_, Some(value)) => value,
+                    (Op::Sum, None) => 0,
+                    (Op::Product, None) => 1,
+                    (_, None) => return Err(format!("{} of no sub-packets", op.name())),
+                };
+                values.try_fold(first, |acc, value| {
+                    op.combine(acc, value)
+                        .ok_or(format!("{} overflows u64", op.name()))
+                })
+            }
+        }
+    }
+
+    /// Folds every subtree that fits in a `u64` into a literal. Subtrees
+    /// that overflow are kept, with nested sums, products, minimums and
+    /// maximums flattened and their literal operands merged.
+    pub fn simplify(&self) -> Packet {
+        let (version, op, packets) = match self {
+            Packet::Literal(_, _) => return self.clone(),
+            Packet::Operator(version, op, packets) => (*version, *op, packets),
+        };
+
+        let mut children = vec![];
+        for packet in packets {
+            match packet.simplify() {
+                Packet::Operator(_, child_op, grandchildren)
+                    if child_op == op && !op.is_comparison() =>
+                {
+                    children.extend(grandchildren)
+                }
+                child => children.push(child),
+            }
+        }
+
+        let packet = Packet::Operator(version, op, children);
+        if let Ok(value) = packet.eval() {
+            return Packet::Literal(version, value);
+        }
+        if op.is_comparison() {
+            return packet;
+        }
+
+        let children = match packet {
+            Packet::Operator(_, _, children) => children,
+            Packet::Literal(_, _) => unreachable!(),
+        };
+        let (literals, mut rest): (Vec<Packet>, Vec<Packet>) = children
+            .into_iter()
+            .partition(|packet| matches!(packet, Packet::Literal(_, _)));
+        let has_zero = literals
+            .iter()
+            .any(|packet| matches!(packet, Packet::Literal(_, 0)));
+        if op == Op::Product && has_zero {
+            return Packet::Literal(version, 0);
+        }
+
+        let mut merged: Vec<u64> = vec![];
+        for packet in literals {
+            if let Packet::Literal(_, value) = packet {
+                match merged.last().and_then(|acc| op.combine(*acc, value)) {
+                    Some(combined) => *merged.last_mut().unwrap() = combined,
+                    None => merged.push(value),
+                }
+            }
+        }
+        let identity = match op {
+            Op::Sum => Some(0),
+            Op::Product => Some(1),
+            _ => None,
+        };
+        for value in merged.into_iter().rev() {
+            if Some(value) != identity {
+                rest.insert(0, Packet::Literal(version, value));
+            }
+        }
+
+        if rest.len() == 1 {
+            rest.pop().unwrap()
+        } else {
+            Packet::Operator(version, op, rest)
+        }
+    }
+}
+
+/// Renders the packet tree as an S-expression, e.g. `(sum 3 (max 1 2))`.
+/// Literals are printed as bare values and versions are omitted.
+impl fmt::Display for Packet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Packet::Literal(_, value) => write!(f, "{}", value),
+            Packet::Operator(_, op, packets) => {
+                write!(f, "({}", op.name())?;
+                for packet in packets {
+                    write!(f, " {}", packet)?;
+                }
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::rng::Rng;
+
+    fn random_packet(rng: &mut Rng, depth: u32) -> Packet {
+        let version = rng.below(8) as u8;
+        if depth == 0 || rng.below(3) == 0 {
+            let bits = rng.below(65);
+            let value = if bits == 64 {
+                rng.next()
+            } else {
+                rng.next() & ((1 << bits) - 1)
+            };
+            return Packet::Literal(version, value);
+        }
+        let op = Op::VALUES[rng.below(7) as usize];
+        let count = match op {
+            Op::GreaterThan | Op::LessThan | Op::EqualTo => 2,
+            _ => 1 + rng.below(4),
+        };
+        let packets = (0..count).map(|_| random_packet(rng, depth - 1)).collect();
+        Packet::Operator(version, op, packets)
+    }
+
+    #[test]
+    fn test_decode_examples() {
+        assert_eq!(Packet::from_hex("D2FE28"), Ok(Packet::Literal(6, 2021)));
+        assert_eq!(
+            Packet::from_hex("38006F45291200").unwrap().to_string(),
+            "(lt 10 20)"
+        );
+        assert_eq!(
+            Packet::from_hex("EE00D40C823060").unwrap().to_string(),
+            "(max 1 2 3)"
+        );
+        assert!(Packet::from_hex("D2FE").is_err());
+        assert!(Packet::from_hex("9C0141080250320F1802104A0G").is_err());
+    }
+
+    #[test]
+    fn test_encode_round_trip() {
+        assert_eq!(Packet::Literal(6, 2021).to_hex(), Ok("D2FE28".to_string()));
+
+        let mut rng = Rng::new(0x2021_1216);
+        for _ in 0..2000 {
+            let packet = random_packet(&mut rng, 4);
+            let hex = packet.to_hex().unwrap();
+            assert_eq!(Packet::from_hex(&hex), Ok(packet));
+        }
+    }
+
+    #[test]
+    fn test_encode_wide_operator() {
+        let packets = vec![Packet::Literal(0, 0); 2048];
+        let packet = Packet::Operator(1, Op::Sum, packets);
+        let hex = packet.to_hex().unwrap();
+        assert_eq!(Packet::from_hex(&hex), Ok(packet));
+    }
+
+    #[test]
+    fn test_eval_and_simplify() {
+        let big = Packet::Literal(0, u64::MAX / 2);
+        let overflow = Packet::Operator(0, Op::Product, vec![big.clone(), Packet::Literal(0, 3)]);
+        assert!(overflow.eval().is_err());
+        assert_eq!(overflow.simplify(), overflow);
+
+        let nested = Packet::Operator(
+            0,
+            Op::Sum,
+            vec![
+                Packet::Literal(0, 1),
+                Packet::Operator(0, Op::Sum, vec![overflow.clone(), Packet::Literal(0, 2)]),
+                Packet::Operator(
+                    0,
+                    Op::Maximum,
+                    vec![Packet::Literal(0, 4), Packet::Literal(0, 5)],
+                ),
+            ],
+        );
+        assert_eq!(
+            nested.simplify().to_string(),
+            format!("(sum 8 {})", overflow)
+        );
+
+        let zero = Packet::Operator(0, Op::Product, vec![overflow, Packet::Literal(0, 0)]);
+        assert_eq!(zero.simplify(), Packet::Literal(0, 0));
+
+        let packet = Packet::from_hex("9C0141080250320F1802104A08").unwrap();
+        assert_eq!(packet.eval(), Ok(1));
+        assert_eq!(packet.simplify(), Packet::Literal(4, 1));
+    }
+}
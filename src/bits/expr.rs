@@ -0,0 +1,212 @@
+//! Compiles a small infix expression language into BITS packets.
+//!
+//! ```text
+//! expr  := sum (('>' | '<' | '==') sum)?
+//! sum   := term ('+' term)*
+//! term  := atom ('*' atom)*
+//! atom  := number | name '(' expr (',' expr)* ')' | '(' expr ')'
+//! ```
+//!
+//! `name` is any op name: `sum`, `product`, `min`, `max`, `gt`, `lt`, `eq`.
+//! Every compiled packet has version 0.
+
+use super::{Op, Packet};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Number(u64),
+    Name(String),
+    Open,
+    Close,
+    Comma,
+    Plus,
+    Star,
+    Greater,
+    Less,
+    Equal,
+}
+
+fn tokenize(s: &str) -> Result<Vec<(usize, Token)>, String> {
+    let chars: Vec<char> = s.chars().collect();
+    let mut tokens = vec![];
+    let mut i = 0;
+    while i < chars.len() {
+        let start = i;
+        let c = chars[i];
+        i += 1;
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            '(' => Token::Open,
+            ')' => Token::Close,
+            ',' => Token::Comma,
+            '+' => Token::Plus,
+            '*' => Token::Star,
+            '>' => Token::Greater,
+            '<' => Token::Less,
+            '=' if chars.get(i) == Some(&'=') => {
+                i += 1;
+                Token::Equal
+            }
+            c if c.is_ascii_digit() => {
+                while i < chars.len() && chars[i].is_ascii_digit() {
+                    i += 1;
+                }
+                let digits: String = chars[start..i].iter().collect();
+                let value = digits
+                    .parse()
+                    .map_err(|_| format!("number too large at column {}", start + 1))?;
+                Token::Number(value)
+            }
+            c if c.is_alphabetic() => {
+                while i < chars.len() && chars[i].is_alphanumeric() {
+                    i += 1;
+                }
+                Token::Name(chars[start..i].iter().collect())
+            }
+            c => return Err(format!("unexpected char '{}' at column {}", c, start + 1)),
+        };
+        tokens.push((start + 1, token));
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    pos: usize,
+    end_column: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(_, token)| token)
+    }
+
+    fn column(&self) -> usize {
+        match self.tokens.get(self.pos) {
+            Some((column, _)) => *column,
+            None => self.end_column,
+        }
+    }
+
+    fn expect(&mut self, expected: Token, description: &str) -> Result<(), String> {
+        if self.peek() == Some(&expected) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(format!(
+                "expected {} at column {}",
+                description,
+                self.column()
+            ))
+        }
+    }
+
+    fn expr(&mut self) -> Result<Packet, String> {
+        let left = self.sum()?;
+        let op = match self.peek() {
+            Some(Token::Greater) => Op::GreaterThan,
+            Some(Token::Less) => Op::LessThan,
+            Some(Token::Equal) => Op::EqualTo,
+            _ => return Ok(left),
+        };
+        self.pos += 1;
+        let right = self.sum()?;
+        Ok(Packet::Operator(0, op, vec![left, right]))
+    }
+
+    fn chain<F>(&mut self, token: Token, op: Op, mut operand: F) -> Result<Packet, String>
+    where
+        F: FnMut(&mut Self) -> Result<Packet, String>,
+    {
+        let mut operands = vec![operand(self)?];
+        while self.peek() == Some(&token) {
+            self.pos += 1;
+            operands.push(operand(self)?);
+        }
+        if operands.len() == 1 {
+            Ok(operands.pop().unwrap())
+        } else {
+            Ok(Packet::Operator(0, op, operands))
+        }
+    }
+
+    fn sum(&mut self) -> Result<Packet, String> {
+        self.chain(Token::Plus, Op::Sum, Parser::term)
+    }
+
+    fn term(&mut self) -> Result<Packet, String> {
+        self.chain(Token::Star, Op::Product, Parser::atom)
+    }
+
+    fn atom(&mut self) -> Result<Packet, String> {
+        let column = self.column();
+        match self.peek().cloned() {
+            Some(Token::Number(value)) => {
+                self.pos += 1;
+                Ok(Packet::Literal(0, value))
+            }
+            Some(Token::Open) => {
+                self.pos += 1;
+                let packet = self.expr()?;
+                self.expect(Token::Close, "')'")?;
+                Ok(packet)
+            }
+            Some(Token::Name(name)) => {
+                let op = Op::from_name(&name)
+                    .ok_or(format!("unknown op '{}' at column {}", name, column))?;
+                self.pos += 1;
+                self.expect(Token::Open, "'('")?;
+                let mut args = vec![self.expr()?];
+                while self.peek() == Some(&Token::Comma) {
+                    self.pos += 1;
+                    args.push(self.expr()?);
+                }
+                self.expect(Token::Close, "')'")?;
+                if op.is_comparison() && args.len() != 2 {
+                    return Err(format!(
+                        "{} takes 2 arguments, found {} at column {}",
+                        name,
+                        args.len(),
+                        column
+                    ));
+                }
+                Ok(Packet::Operator(0, op, args))
+            }
+            _ => Err(format!("expected a value at column {}", column)),
+        }
+    }
+}
+
+pub fn compile(s: &str) -> Result<Packet, String> {
+    let mut parser = Parser {
+        tokens: tokenize(s)?,
+        pos: 0,
+        end_column: s.chars().count() + 1,
+    };
+    let packet = parser.expr()?;
+    if parser.pos != parser.tokens.len() {
+        return Err(format!("unexpected input at column {}", parser.column()));
+    }
+    Ok(packet)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_compile() {
+        let packet = compile("sum(1, max(2,3)) > 4").unwrap();
+        assert_eq!(packet.to_string(), "(gt (sum 1 (max 2 3)) 4)");
+        assert_eq!(packet.eval(), Ok(0));
+
+        let packet = compile("1 + 2 * (3 + 4) == 15").unwrap();
+        assert_eq!(packet.to_string(), "(eq (sum 1 (product 2 (sum 3 4))) 15)");
+        assert_eq!(Packet::from_hex(&packet.to_hex().unwrap()), Ok(packet));
+
+        assert!(compile("gt(1)").is_err());
+        assert!(compile("sum(1, 2").is_err());
+        assert!(compile("avg(1, 2)").is_err());
+        assert!(compile("1 + 2 3").is_err());
+    }
+}
@@ -0,0 +1,274 @@
+//! Writing grids out as images, without any dependencies: plain PNM (P1 for
+//! black and white, P3 for colour) with one pixel per cell, or SVG with one
+//! unit square per cell.
+//!
+//! An `Overlay` of points and paths can be drawn over the cells in a single
+//! colour. In PNM paths are rasterised; in SVG they're lines through the
+//! centres of their cells.
+
+use crate::grid::GridView;
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+pub type Rgb = (u8, u8, u8);
+
+pub const BLACK: Rgb = (0, 0, 0);
+pub const WHITE: Rgb = (255, 255, 255);
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Overlay {
+    pub color: Rgb,
+    pub points: Vec<(isize, isize)>,
+    /// Each path runs in straight lines from one position to the next.
+    pub paths: Vec<Vec<(isize, isize)>>,
+}
+
+impl Overlay {
+    fn cells(&self) -> HashSet<(isize, isize)> {
+        let mut cells: HashSet<(isize, isize)> = self.points.iter().copied().collect();
+        for path in &self.paths {
+            cells.extend(path.iter().copied());
+            for pair in path.windows(2) {
+                cells.extend(line(pair[0], pair[1]));
+            }
+        }
+        cells
+    }
+}
+
+// the cells on the line from a to b, stepping along the longer axis.
+fn line(a: (isize, isize), b: (isize, isize)) -> Vec<(isize, isize)> {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let steps = dx.abs().max(dy.abs());
+    if steps == 0 {
+        return vec![a];
+    }
+    let at = |d: isize, i: isize| (2 * d * i + steps).div_euclid(2 * steps);
+    (0..=steps)
+        .map(|i| (a.0 + at(dx, i), a.1 + at(dy, i)))
+        .collect()
+}
+
+fn hex((r, g, b): Rgb) -> String {
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+// every cell's colour row by row, with the overlay on top.
+fn pixels<G, F>(grid: &G, color: F, overlay: Option<&Overlay>) -> Vec<Vec<Rgb>>
+where
+    G: GridView,
+    F: Fn((isize, isize), G::Item) -> Rgb,
+{
+    let cells = overlay.map(Overlay::cells).unwrap_or_default();
+    (0..grid.height() as isize)
+        .map(|y| {
+            (0..grid.width() as isize)
+                .map(|x| match overlay {
+                    Some(overlay) if cells.contains(&(x, y)) => overlay.color,
+                    _ => color((x, y), grid.get((x, y)).unwrap()),
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Plain PBM, where 1 is a black pixel. `on` picks the black cells from
+/// their position and value, and overlaid cells are black too.
+pub fn to_pbm<G, F>(grid: &G, on: F, overlay: Option<&Overlay>) -> String
+where
+    G: GridView,
+    F: Fn((isize, isize), G::Item) -> bool,
+{
+    let overlay = overlay.map(|overlay| Overlay {
+        color: BLACK,
+        ..overlay.clone()
+    });
+    let pixels = pixels(
+        grid,
+        |pos, v| if on(pos, v) { BLACK } else { WHITE },
+        overlay.as_ref(),
+    );
+    let mut result = format!("P1\n{} {}\n", grid.width(), grid.height());
+    for row in pixels {
+        let row: Vec<&str> = row
+            .iter()
+            .map(|&pixel| if pixel == BLACK { "1" } else { "0" })
+            .collect();
+        result.push_str(&row.join(" "));
+        result.push('\n');
+    }
+    result
+}
+
+/// Plain PPM with a maximum value of 255.
+pub fn to_ppm<G, F>(grid: &G, color: F, overlay: Option<&Overlay>) -> String
+where
+    G: GridView,
+    F: Fn((isize, isize), G::Item) -> Rgb,
+{
+    let mut result = format!("P3\n{} {}\n255\n", grid.width(), grid.height());
+    for row in pixels(grid, color, overlay) {
+        let row: Vec<String> = row
+            .iter()
+            .map(|(r, g, b)| format!("{} {} {}", r, g, b))
+            .collect();
+        result.push_str(&row.join("  "));
+        result.push('\n');
+    }
+    result
+}
+
+/// An SVG drawing ten pixels to a cell, with runs of same-coloured cells
+/// merged into one rectangle.
+pub fn to_svg<G, F>(grid: &G, color: F, overlay: Option<&Overlay>) -> String
+where
+    G: GridView,
+    F: Fn((isize, isize), G::Item) -> Rgb,
+{
+    let (width, height) = (grid.width(), grid.height());
+    let mut lines = vec![
+        format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 {} {}\" width=\"{}\" height=\"{}\">",
+            width,
+            height,
+            width * 10,
+            height * 10
+        ),
+        "<g shape-rendering=\"crispEdges\">".to_string(),
+    ];
+    for y in 0..height as isize {
+        let mut x = 0;
+        while x < width as isize {
+            let fill = color((x, y), grid.get((x, y)).unwrap());
+            let start = x;
+            while x < width as isize && color((x, y), grid.get((x, y)).unwrap()) == fill {
+                x += 1;
+            }
+            lines.push(format!(
+                "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"1\" fill=\"{}\"/>",
+                start,
+                y,
+                x - start,
+                hex(fill)
+            ));
+        }
+    }
+    if let Some(overlay) = overlay {
+        for (x, y) in &overlay.points {
+            lines.push(format!(
+                "<rect x=\"{}\" y=\"{}\" width=\"1\" height=\"1\" fill=\"{}\"/>",
+                x,
+                y,
+                hex(overlay.color)
+            ));
+        }
+    }
+    lines.push("</g>".to_string());
+    if let Some(overlay) = overlay {
+        for path in &overlay.paths {
+            let points: Vec<String> = path
+                .iter()
+                .map(|&(x, y)| format!("{},{}", x as f64 + 0.5, y as f64 + 0.5))
+                .collect();
+            lines.push(format!(
+                "<polyline points=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"0.3\" stroke-linecap=\"round\" stroke-linejoin=\"round\"/>",
+                points.join(" "),
+                hex(overlay.color)
+            ));
+        }
+    }
+    lines.push("</svg>".to_string());
+    lines.join("\n") + "\n"
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Pbm,
+    Ppm,
+    Svg,
+}
+
+impl Format {
+    /// The format named by a path's extension.
+    pub fn from_path(path: &str) -> Result<Self, String> {
+        let extension = Path::new(path)
+            .extension()
+            .and_then(|extension| extension.to_str());
+        match extension {
+            Some("pbm") => Ok(Format::Pbm),
+            Some("ppm") => Ok(Format::Ppm),
+            Some("svg") => Ok(Format::Svg),
+            _ => Err(format!("expected a .pbm, .ppm or .svg path: {}", path)),
+        }
+    }
+}
+
+/// Writes the grid in the format `path`'s extension names. In PBM a cell is
+/// black if its colour is darker than mid grey.
+pub fn write<G, F>(path: &str, grid: &G, color: F, overlay: Option<&Overlay>) -> Result<(), String>
+where
+    G: GridView,
+    F: Fn((isize, isize), G::Item) -> Rgb,
+{
+    let image = match Format::from_path(path)? {
+        Format::Pbm => {
+            let dark = |pos, v| {
+                let (r, g, b) = color(pos, v);
+                (r as u32 + g as u32 + b as u32) < 384
+            };
+            to_pbm(grid, dark, overlay)
+        }
+        Format::Ppm => to_ppm(grid, color, overlay),
+        Format::Svg => to_svg(grid, color, overlay),
+    };
+    fs::write(path, image).map_err(|e| format!("{}: {}", path, e))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::grid::Grid;
+
+    #[test]
+    fn test_export() {
+        assert_eq!(line((0, 0), (3, 1)), [(0, 0), (1, 0), (2, 1), (3, 1)]);
+        assert_eq!(line((2, 2), (0, 0)), [(2, 2), (1, 1), (0, 0)]);
+
+        let grid = Grid::new(3, 2, vec![0u8, 0, 1, 1, 0, 0]);
+        let color = |_, v: u8| if v == 1 { BLACK } else { WHITE };
+        assert_eq!(
+            to_pbm(&grid, |_, v| v == 1, None),
+            "P1\n3 2\n0 0 1\n1 0 0\n"
+        );
+        let overlay = Overlay {
+            color: (255, 0, 0),
+            points: vec![],
+            paths: vec![vec![(0, 0), (1, 1)]],
+        };
+        assert_eq!(
+            to_pbm(&grid, |_, v| v == 1, Some(&overlay)),
+            "P1\n3 2\n1 0 1\n1 1 0\n"
+        );
+        assert_eq!(
+            to_ppm(&grid, color, Some(&overlay)),
+            "P3\n3 2\n255\n255 0 0  255 255 255  0 0 0\n0 0 0  255 0 0  255 255 255\n"
+        );
+
+        let svg = to_svg(&grid, color, Some(&overlay));
+        assert!(svg.contains("<rect x=\"0\" y=\"0\" width=\"2\" height=\"1\" fill=\"#ffffff\"/>"));
+        assert!(svg.contains("<rect x=\"1\" y=\"1\" width=\"2\" height=\"1\" fill=\"#ffffff\"/>"));
+        assert!(svg.contains("<polyline points=\"0.5,0.5 1.5,1.5\""));
+        let overlay = Overlay {
+            paths: vec![vec![(-1, 0), (0, -2)]],
+            ..overlay
+        };
+        let svg = to_svg(&grid, color, Some(&overlay));
+        assert!(svg.contains("<polyline points=\"-0.5,0.5 0.5,-1.5\""));
+        assert_eq!(svg.matches("<rect").count(), 4);
+
+        assert_eq!(Format::from_path("out/basins.svg"), Ok(Format::Svg));
+        assert!(Format::from_path("basins.png").is_err());
+        assert!(Format::from_path("basins").is_err());
+    }
+}
@@ -0,0 +1,147 @@
+/// Read-only access to a rectangular grid of values.
+///
+/// Positions are signed so callers can probe neighbours without
+/// bounds-checking first; anything outside the grid is `None`.
+pub trait GridView {
+    type Item;
+
+    fn width(&self) -> usize;
+
+    fn height(&self) -> usize;
+
+    fn get(&self, pos: (isize, isize)) -> Option<Self::Item>;
+
+    fn contains(&self, pos: (isize, isize)) -> bool {
+        let (x, y) = pos;
+        x >= 0 && y >= 0 && (x as usize) < self.width() && (y as usize) < self.height()
+    }
+
+    fn neighbors(&self, pos: (isize, isize)) -> Vec<(isize, isize)> {
+        let (x, y) = pos;
+        [(x, y - 1), (x, y + 1), (x - 1, y), (x + 1, y)]
+            .into_iter()
+            .filter(|pos| self.contains(*pos))
+            .collect()
+    }
+}
+
+/// A dense row-major grid.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    data: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    pub fn new(width: usize, height: usize, data: Vec<T>) -> Self {
+        assert_eq!(
+            width * height,
+            data.len(),
+            "grid data does not match dimensions"
+        );
+        Self {
+            width,
+            height,
+            data,
+        }
+    }
+}
+
+impl<T: Copy> GridView for Grid<T> {
+    type Item = T;
+
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    fn height(&self) -> usize {
+        self.height
+    }
+
+    fn get(&self, pos: (isize, isize)) -> Option<T> {
+        if self.contains(pos) {
+            let (x, y) = pos;
+            Some(self.data[x as usize + y as usize * self.width])
+        } else {
+            None
+        }
+    }
+}
+
+/// Presents `tiles_x` by `tiles_y` copies of a base grid as one larger grid.
+///
+/// Every cell is computed on demand as `transform(base_value, (tile_x, tile_y))`,
+/// so repeating a map costs nothing beyond the base grid itself.
+pub struct TiledGrid<'a, G, F> {
+    base: &'a G,
+    tiles_x: usize,
+    tiles_y: usize,
+    transform: F,
+}
+
+impl<'a, G, F> TiledGrid<'a, G, F>
+where
+    G: GridView,
+    F: Fn(G::Item, (usize, usize)) -> G::Item,
+{
+    pub fn new(base: &'a G, tiles_x: usize, tiles_y: usize, transform: F) -> Self {
+        Self {
+            base,
+            tiles_x,
+            tiles_y,
+            transform,
+        }
+    }
+}
+
+impl<'a, G, F> GridView for TiledGrid<'a, G, F>
+where
+    G: GridView,
+    F: Fn(G::Item, (usize, usize)) -> G::Item,
+{
+    type Item = G::Item;
+
+    fn width(&self) -> usize {
+        self.base.width() * self.tiles_x
+    }
+
+    fn height(&self) -> usize {
+        self.base.height() * self.tiles_y
+    }
+
+    fn get(&self, pos: (isize, isize)) -> Option<G::Item> {
+        if !self.contains(pos) {
+            return None;
+        }
+        let (x, y) = (pos.0 as usize, pos.1 as usize);
+        let (base_width, base_height) = (self.base.width(), self.base.height());
+        let tile = (x / base_width, y / base_height);
+        let base_pos = ((x % base_width) as isize, (y % base_height) as isize);
+        self.base
+            .get(base_pos)
+            .map(|value| (self.transform)(value, tile))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_tiled_grid() {
+        let base = Grid::new(2, 1, vec![8u8, 9]);
+        let tiled = TiledGrid::new(&base, 3, 2, |value, (tx, ty)| {
+            (value + (tx + ty) as u8 - 1) % 9 + 1
+        });
+
+        assert_eq!(tiled.width(), 6);
+        assert_eq!(tiled.height(), 2);
+        assert_eq!(tiled.get((0, 0)), Some(8));
+        assert_eq!(tiled.get((2, 0)), Some(9));
+        assert_eq!(tiled.get((3, 0)), Some(1));
+        assert_eq!(tiled.get((5, 1)), Some(3));
+        assert_eq!(tiled.get((6, 0)), None);
+        assert_eq!(tiled.get((-1, 0)), None);
+    }
+}
@@ -1 +1,259 @@
-// TODO
+//! Parsing the puzzle inputs.
+//!
+//! The parsers here each take a piece of text and return a plain `String`
+//! error, and combine by passing one to another: `list(s, ",", number)`.
+//! `lines` and `Section::parse_lines` run a parser over every line and say
+//! where it failed, as in `line 3: invalid number 'x' in: 1,x`.
+
+use std::fs;
+use std::str::FromStr;
+
+/// The puzzle input for a day, from `inputs/NN.txt`.
+pub fn read_input(day: u32) -> String {
+    read_file(&format!("inputs/{:02}.txt", day))
+}
+
+/// An input from anywhere else, such as a path given on the command line.
+pub fn read_file(path: &str) -> String {
+    fs::read_to_string(path).unwrap_or_else(|e| panic!("{}: {}", path, e))
+}
+
+/// An integer, or anything else that parses with `FromStr`.
+pub fn number<T: FromStr>(s: &str) -> Result<T, String> {
+    s.parse().map_err(|_| format!("invalid number '{}'", s))
+}
+
+/// The decimal digits of `s`, such as a row of a heightmap.
+pub fn digits(s: &str) -> Result<Vec<u8>, String> {
+    s.chars()
+        .map(|c| match c.to_digit(10) {
+            Some(d) => Ok(d as u8),
+            None => Err(format!("invalid digit '{}'", c)),
+        })
+        .collect()
+}
+
+/// Items separated by `sep`, such as `3,4,3,1,2`.
+pub fn list<'a, T, F>(s: &'a str, sep: &str, item: F) -> Result<Vec<T>, String>
+where
+    F: FnMut(&'a str) -> Result<T, String>,
+{
+    s.split(sep).map(item).collect()
+}
+
+/// Items separated by runs of whitespace, ignoring any at either end.
+pub fn words<'a, T, F>(s: &'a str, item: F) -> Result<Vec<T>, String>
+where
+    F: FnMut(&'a str) -> Result<T, String>,
+{
+    s.split_whitespace().map(item).collect()
+}
+
+/// The text either side of the first `sep`.
+pub fn split_pair<'a>(s: &'a str, sep: &str) -> Result<(&'a str, &'a str), String> {
+    s.split_once(sep)
+        .ok_or_else(|| format!("missing '{}'", sep.trim()))
+}
+
+/// Two items separated by `sep`, such as the point `0,9`.
+pub fn pair<'a, T, F>(s: &'a str, sep: &str, mut item: F) -> Result<(T, T), String>
+where
+    F: FnMut(&'a str) -> Result<T, String>,
+{
+    let (a, b) = split_pair(s, sep)?;
+    Ok((item(a)?, item(b)?))
+}
+
+/// A `key -> value` rule, such as `CH -> B` or `0,9 -> 5,9`.
+pub fn rule<'a, K, V, F, G>(s: &'a str, key: F, value: G) -> Result<(K, V), String>
+where
+    F: FnOnce(&'a str) -> Result<K, String>,
+    G: FnOnce(&'a str) -> Result<V, String>,
+{
+    let (k, v) = split_pair(s, " -> ")?;
+    Ok((key(k)?, value(v)?))
+}
+
+/// A named inclusive range `x=a..b`, returned as the name and its bounds.
+pub fn range<T: FromStr>(s: &str) -> Result<(&str, (T, T)), String> {
+    let (name, bounds) = split_pair(s, "=")?;
+    let (start, end) = pair(bounds, "..", number)?;
+    Ok((name, (start, end)))
+}
+
+// prefixes an error with the line it came from.
+fn at_line(number: usize, line: &str, e: String) -> String {
+    format!("line {}: {} in: {}", number, e, line)
+}
+
+/// Every line parsed with `item`.
+pub fn lines<'a, T, F>(input: &'a str, item: F) -> Result<Vec<T>, String>
+where
+    F: FnMut(&'a str) -> Result<T, String>,
+{
+    Section::new(input, 1).parse_lines(item)
+}
+
+/// The input's only line parsed with `item`.
+pub fn line<'a, T, F>(input: &'a str, item: F) -> Result<T, String>
+where
+    F: FnOnce(&'a str) -> Result<T, String>,
+{
+    Section::new(input, 1).parse_line(item)
+}
+
+/// A run of lines between blank lines, which remembers where it started in
+/// the input so that its errors give the right line numbers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Section<'a> {
+    text: &'a str,
+    first_line: usize,
+}
+
+impl<'a> Section<'a> {
+    fn new(text: &'a str, first_line: usize) -> Self {
+        Section { text, first_line }
+    }
+
+    pub fn text(&self) -> &'a str {
+        self.text
+    }
+
+    /// The line number of the section's first line, counting from 1.
+    pub fn first_line(&self) -> usize {
+        self.first_line
+    }
+
+    fn numbered(&self) -> impl Iterator<Item = (usize, &'a str)> {
+        let first_line = self.first_line;
+        self.text
+            .lines()
+            .enumerate()
+            .map(move |(i, line)| (first_line + i, line))
+    }
+
+    /// The section's first line, and the rest of it.
+    pub fn split_first(&self) -> (&'a str, Section<'a>) {
+        match self.text.split_once('\n') {
+            Some((first, rest)) => (first, Section::new(rest, self.first_line + 1)),
+            None => (self.text, Section::new("", self.first_line + 1)),
+        }
+    }
+
+    /// Every line of the section parsed with `item`.
+    pub fn parse_lines<T, F>(&self, mut item: F) -> Result<Vec<T>, String>
+    where
+        F: FnMut(&'a str) -> Result<T, String>,
+    {
+        self.numbered()
+            .map(|(number, line)| item(line).map_err(|e| at_line(number, line, e)))
+            .collect()
+    }
+
+    /// The section's only line parsed with `item`.
+    pub fn parse_line<T, F>(&self, item: F) -> Result<T, String>
+    where
+        F: FnOnce(&'a str) -> Result<T, String>,
+    {
+        let mut lines = self.numbered();
+        match (lines.next(), lines.next()) {
+            (Some((number, line)), None) => item(line).map_err(|e| at_line(number, line, e)),
+            (None, _) => Err(format!("line {}: expected a line", self.first_line)),
+            (Some(_), Some((number, line))) => {
+                Err(at_line(number, line, "expected a blank line".to_string()))
+            }
+        }
+    }
+}
+
+/// The input split at blank lines. Runs of blank lines count as one, and
+/// blank lines at either end are ignored.
+pub fn sections(input: &str) -> Vec<Section<'_>> {
+    let mut sections = vec![];
+    let mut start = None;
+    let mut offset = 0;
+    for (i, line) in input.split_inclusive('\n').enumerate() {
+        let blank = line.trim().is_empty();
+        match start {
+            None if !blank => start = Some((offset, i + 1)),
+            Some((from, first_line)) if blank => {
+                sections.push(Section::new(&input[from..offset], first_line));
+                start = None;
+            }
+            _ => (),
+        }
+        offset += line.len();
+    }
+    if let Some((from, first_line)) = start {
+        sections.push(Section::new(&input[from..], first_line));
+    }
+    sections
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parsers() {
+        assert_eq!(list("3,4,3", ",", number::<u8>), Ok(vec![3, 4, 3]));
+        assert_eq!(
+            list("3,,4", ",", number::<u8>),
+            Err("invalid number ''".to_string())
+        );
+        assert_eq!(words(" 22 13  17", number::<u32>), Ok(vec![22, 13, 17]));
+        assert_eq!(digits("2199"), Ok(vec![2, 1, 9, 9]));
+        assert!(digits("21a").is_err());
+        assert_eq!(pair("0,9", ",", number::<i32>), Ok((0, 9)));
+        assert_eq!(
+            pair("0 9", ",", number::<i32>),
+            Err("missing ','".to_string())
+        );
+        assert_eq!(
+            rule(
+                "0,9 -> 5,9",
+                |s| pair(s, ",", number::<i32>),
+                |s| pair(s, ",", number::<i32>)
+            ),
+            Ok(((0, 9), (5, 9)))
+        );
+        assert_eq!(
+            rule("CH B", Ok::<_, String>, Ok::<_, String>),
+            Err("missing '->'".to_string())
+        );
+        assert_eq!(range::<i64>("x=-20..26"), Ok(("x", (-20, 26))));
+        assert_eq!(range::<i64>("x=-20.26"), Err("missing '..'".to_string()));
+
+        assert_eq!(line("7,4\n", |s| pair(s, ",", number::<u8>)), Ok((7, 4)));
+        assert_eq!(
+            lines("1,2\n3,x", |s| pair(s, ",", number::<u8>)),
+            Err("line 2: invalid number 'x' in: 3,x".to_string())
+        );
+        let input = "\n7,4,9\n\n\n22 13\n 8  2\n\n1 x\n";
+        let sections = sections(input);
+        assert_eq!(sections.len(), 3);
+        assert_eq!(
+            sections[0].parse_line(|s| list(s, ",", number::<u8>)),
+            Ok(vec![7, 4, 9])
+        );
+        assert_eq!(
+            sections[1].parse_lines(|s| words(s, number::<u8>)),
+            Ok(vec![vec![22, 13], vec![8, 2]])
+        );
+        assert_eq!(
+            sections[1].parse_line(Ok),
+            Err("line 6: expected a blank line in:  8  2".to_string())
+        );
+        assert_eq!(
+            sections[2].parse_lines(|s| words(s, number::<u8>)),
+            Err("line 8: invalid number 'x' in: 1 x".to_string())
+        );
+        let (header, body) = sections[1].split_first();
+        assert_eq!(header, "22 13");
+        assert_eq!(body.text(), " 8  2\n");
+        assert_eq!(
+            body.parse_lines(number::<u8>),
+            Err("line 6: invalid number ' 8  2' in:  8  2".to_string())
+        );
+    }
+}
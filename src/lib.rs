@@ -1 +1,11 @@
+pub mod alu;
+pub mod automaton;
+pub mod bits;
+pub mod export;
+pub mod grid;
 pub mod helpers;
+pub mod parallel;
+pub mod render;
+pub mod rng;
+pub mod runner;
+pub mod snailfish;
@@ -0,0 +1,107 @@
+//! Splitting work across threads with `std::thread::scope`. Results come
+//! back in the order of the input, so output never depends on scheduling.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+/// The `--jobs N` option, defaulting to the number of CPUs available.
+pub fn jobs_from_args(args: &[String]) -> Result<usize, String> {
+    match args.iter().position(|arg| arg == "--jobs") {
+        Some(i) => {
+            let jobs = args.get(i + 1).ok_or("--jobs needs a thread count")?;
+            match jobs.parse() {
+                Ok(0) | Err(_) => Err(format!("invalid thread count '{}'", jobs)),
+                Ok(jobs) => Ok(jobs),
+            }
+        }
+        None => Ok(thread::available_parallelism().map_or(1, |n| n.get())),
+    }
+}
+
+/// `f` applied to every item, with the items split into `jobs` contiguous
+/// chunks.
+pub fn map<T, R, F>(items: &[T], jobs: usize, f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    if jobs <= 1 || items.len() <= 1 {
+        return items.iter().map(f).collect();
+    }
+    let chunk_size = items.len().div_ceil(jobs);
+    let f = &f;
+    thread::scope(|scope| {
+        let handles: Vec<_> = items
+            .chunks(chunk_size)
+            .map(|chunk| scope.spawn(move || chunk.iter().map(f).collect::<Vec<R>>()))
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap())
+            .collect()
+    })
+}
+
+/// The first `Some` that `f` returns, trying items in order. Thread `i` of
+/// `jobs` takes items `i`, `i + jobs`, ... and stops once an earlier item has
+/// already matched, so the answer is the same as a sequential search.
+pub fn find_map_first<T, R, F>(items: &[T], jobs: usize, f: F) -> Option<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> Option<R> + Sync,
+{
+    if jobs <= 1 || items.len() <= 1 {
+        return items.iter().find_map(f);
+    }
+    let best = AtomicUsize::new(usize::MAX);
+    let (f, best) = (&f, &best);
+    thread::scope(|scope| {
+        let handles: Vec<_> = (0..jobs.min(items.len()))
+            .map(|start| {
+                scope.spawn(move || {
+                    for i in (start..items.len()).step_by(jobs) {
+                        if i > best.load(Ordering::Relaxed) {
+                            break;
+                        }
+                        if let Some(result) = f(&items[i]) {
+                            best.fetch_min(i, Ordering::Relaxed);
+                            return Some((i, result));
+                        }
+                    }
+                    None
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .filter_map(|handle| handle.join().unwrap())
+            .min_by_key(|(i, _)| *i)
+            .map(|(_, result)| result)
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parallel() {
+        let items: Vec<u64> = (0..1000).collect();
+        let squares: Vec<u64> = items.iter().map(|n| n * n).collect();
+        for jobs in [1, 3, 8, 2000] {
+            assert_eq!(map(&items, jobs, |n| n * n), squares);
+            let first = find_map_first(&items, jobs, |&n| (n > 10 && n % 7 == 0).then_some(n));
+            assert_eq!(first, Some(14));
+            assert_eq!(find_map_first(&items, jobs, |_| None::<u64>), None);
+        }
+        assert!(map(&[] as &[u64], 4, |n| *n).is_empty());
+
+        let args = |s: &str| -> Vec<String> { s.split(' ').map(String::from).collect() };
+        assert_eq!(jobs_from_args(&args("--json --jobs 3")), Ok(3));
+        assert!(jobs_from_args(&args("--json")).unwrap() >= 1);
+        assert!(jobs_from_args(&args("--jobs 0")).is_err());
+        assert!(jobs_from_args(&args("--jobs")).is_err());
+    }
+}
@@ -0,0 +1,189 @@
+//! Drawing grids in the terminal, behind each day's `--visualize` flag.
+//!
+//! Every cell becomes a `Glyph`: a character and an optional level in
+//! `0.0..=1.0` that a `ColorMap` turns into a 24-bit ANSI colour. When stdout
+//! isn't a terminal, or `NO_COLOR` is set, frames are plain ASCII and are
+//! printed one after another without pausing.
+
+use crate::grid::GridView;
+use std::env;
+use std::io::{self, IsTerminal, Write};
+use std::thread;
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorMap {
+    /// Deep water up through grass to snow, for heightmaps.
+    Terrain,
+    /// Black through red and yellow to white, for energy and costs.
+    Heat,
+    /// Black to white.
+    Gray,
+}
+
+impl ColorMap {
+    fn stops(self) -> &'static [(u8, u8, u8)] {
+        match self {
+            ColorMap::Terrain => &[
+                (20, 40, 120),
+                (40, 140, 70),
+                (150, 120, 60),
+                (240, 240, 240),
+            ],
+            ColorMap::Heat => &[(0, 0, 0), (200, 30, 0), (255, 200, 0), (255, 255, 255)],
+            ColorMap::Gray => &[(0, 0, 0), (255, 255, 255)],
+        }
+    }
+
+    /// The colour at `level`, clamped to `0.0..=1.0` and interpolated
+    /// between the map's stops.
+    pub fn rgb(self, level: f64) -> (u8, u8, u8) {
+        let stops = self.stops();
+        let t = level.clamp(0.0, 1.0) * (stops.len() - 1) as f64;
+        let i = (t as usize).min(stops.len() - 2);
+        let f = t - i as f64;
+        let mix = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * f).round() as u8;
+        let ((r0, g0, b0), (r1, g1, b1)) = (stops[i], stops[i + 1]);
+        (mix(r0, r1), mix(g0, g1), mix(b0, b1))
+    }
+}
+
+/// What to draw for one cell.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Glyph {
+    pub ch: char,
+    /// Where the cell falls on the colour map, or `None` for the terminal's
+    /// default colour.
+    pub level: Option<f64>,
+}
+
+impl Glyph {
+    pub fn plain(ch: char) -> Self {
+        Glyph { ch, level: None }
+    }
+
+    pub fn colored(ch: char, level: f64) -> Self {
+        Glyph {
+            ch,
+            level: Some(level),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Renderer {
+    color: bool,
+    delay: Duration,
+}
+
+impl Renderer {
+    pub fn new(color: bool, delay: Duration) -> Self {
+        Renderer { color, delay }
+    }
+
+    /// `Some` when the arguments include `--visualize`. `--delay <ms>` sets
+    /// the pause between animation frames, 50ms by default.
+    pub fn from_args(args: &[String]) -> Result<Option<Self>, String> {
+        if !args.iter().any(|arg| arg == "--visualize") {
+            return Ok(None);
+        }
+        let mut delay = Duration::from_millis(50);
+        if let Some(i) = args.iter().position(|arg| arg == "--delay") {
+            let ms = args.get(i + 1).ok_or("--delay needs a value in ms")?;
+            let ms = ms.parse().map_err(|_| format!("invalid delay '{}'", ms))?;
+            delay = Duration::from_millis(ms);
+        }
+        Ok(Some(Renderer::for_stdout(delay)))
+    }
+
+    /// A renderer that only uses colour and animation if stdout is a
+    /// terminal and `NO_COLOR` isn't set.
+    pub fn for_stdout(delay: Duration) -> Self {
+        let color = io::stdout().is_terminal() && env::var_os("NO_COLOR").is_none();
+        Renderer::new(color, delay)
+    }
+
+    /// Draws every cell of the grid through `glyph`, one line per row.
+    pub fn draw<G, F>(&self, grid: &G, map: ColorMap, glyph: F) -> String
+    where
+        G: GridView,
+        F: Fn((isize, isize), G::Item) -> Glyph,
+    {
+        let mut result = String::new();
+        for y in 0..grid.height() as isize {
+            let mut current = None;
+            for x in 0..grid.width() as isize {
+                let pos = (x, y);
+                let glyph = glyph(pos, grid.get(pos).unwrap());
+                if self.color {
+                    let color = glyph.level.map(|level| map.rgb(level));
+                    if color != current {
+                        match color {
+                            Some((r, g, b)) => result += &format!("\x1b[38;2;{};{};{}m", r, g, b),
+                            None => result += "\x1b[0m",
+                        }
+                        current = color;
+                    }
+                }
+                result.push(glyph.ch);
+            }
+            if self.color && current.is_some() {
+                result += "\x1b[0m";
+            }
+            result.push('\n');
+        }
+        result
+    }
+
+    /// Prints a frame under a title. On a terminal the screen is cleared
+    /// first and the frame stays up for the delay.
+    pub fn show(&self, title: &str, frame: &str) {
+        let mut stdout = io::stdout().lock();
+        if self.color {
+            write!(stdout, "\x1b[2J\x1b[H").unwrap();
+        }
+        writeln!(stdout, "{}\n{}", title, frame).unwrap();
+        stdout.flush().unwrap();
+        if self.color {
+            thread::sleep(self.delay);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::grid::Grid;
+
+    #[test]
+    fn test_draw() {
+        assert_eq!(ColorMap::Gray.rgb(0.0), (0, 0, 0));
+        assert_eq!(ColorMap::Gray.rgb(0.5), (128, 128, 128));
+        assert_eq!(ColorMap::Heat.rgb(2.0), (255, 255, 255));
+        assert_eq!(ColorMap::Terrain.rgb(-1.0), (20, 40, 120));
+
+        let grid = Grid::new(3, 2, vec![0u8, 5, 9, 9, 5, 0]);
+        let glyph = |_, v: u8| {
+            if v == 0 {
+                Glyph::plain('*')
+            } else {
+                Glyph::colored(char::from(b'0' + v), v as f64 / 9.0)
+            }
+        };
+        let plain = Renderer::new(false, Duration::ZERO);
+        assert_eq!(plain.draw(&grid, ColorMap::Gray, glyph), "*59\n95*\n");
+        let color = Renderer::new(true, Duration::ZERO);
+        assert_eq!(
+            color.draw(&grid, ColorMap::Gray, glyph),
+            "*\x1b[38;2;142;142;142m5\x1b[38;2;255;255;255m9\x1b[0m\n\
+             \x1b[38;2;255;255;255m9\x1b[38;2;142;142;142m5\x1b[0m*\n"
+        );
+
+        let args = |s: &str| -> Vec<String> { s.split(' ').map(String::from).collect() };
+        assert_eq!(Renderer::from_args(&args("--pbm 2 out.pbm")), Ok(None));
+        let renderer = Renderer::from_args(&args("--visualize --delay 5")).unwrap();
+        assert_eq!(renderer.unwrap().delay, Duration::from_millis(5));
+        assert!(Renderer::from_args(&args("--visualize --delay")).is_err());
+        assert!(Renderer::from_args(&args("--visualize --delay soon")).is_err());
+    }
+}
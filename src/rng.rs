@@ -0,0 +1,29 @@
+//! A small seeded xorshift generator for tests that check one implementation
+//! against another on random inputs. The same seed always gives the same
+//! inputs, so a failure can be reproduced.
+//!
+//! It isn't behind `cfg(test)` because the days' own tests link against the
+//! library built normally.
+
+#[derive(Debug, Clone)]
+pub struct Rng(u64);
+
+impl Rng {
+    /// A generator starting from `seed`, which must not be zero.
+    pub fn new(seed: u64) -> Self {
+        assert_ne!(seed, 0, "xorshift needs a non-zero seed");
+        Rng(seed)
+    }
+
+    pub fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// A number in `0..n`.
+    pub fn below(&mut self, n: u64) -> u64 {
+        self.next() % n
+    }
+}
@@ -0,0 +1,227 @@
+//! Running a day's parts and reporting what they find.
+//!
+//! Answers print as the usual `part1: N` lines, or with `--json` as one
+//! object per line such as `{"day":3,"part":2,"answer":793873,"time_ms":0.412}`.
+//! Intermediate results reported with `Runner::note` come out the same way,
+//! as `label: value` or `{"day":19,"label":"scanner 1","value":"..."}`, and
+//! work that both parts share is timed on its own with `Runner::shared`, as
+//! `align scanners: 1.234 ms` or `{"day":19,"label":"align scanners","time_ms":1.234}`.
+
+use std::env;
+use std::fmt;
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+
+/// A part's answer: a number, or text such as day 13's folded letters.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Answer {
+    Number(i128),
+    Text(String),
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Answer::Number(n) => write!(f, "{}", n),
+            Answer::Text(s) => write!(f, "{}", s),
+        }
+    }
+}
+
+impl Answer {
+    fn to_json(&self) -> String {
+        match self {
+            Answer::Number(n) => n.to_string(),
+            Answer::Text(s) => json_string(s),
+        }
+    }
+}
+
+macro_rules! number_from {
+    ($($t:ty),*) => {
+        $(impl From<$t> for Answer {
+            fn from(n: $t) -> Self {
+                Answer::Number(n as i128)
+            }
+        })*
+    };
+}
+
+number_from!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize, i128);
+
+impl From<u128> for Answer {
+    fn from(n: u128) -> Self {
+        Answer::Number(i128::try_from(n).expect("answer doesn't fit in an i128"))
+    }
+}
+
+impl From<String> for Answer {
+    fn from(s: String) -> Self {
+        Answer::Text(s)
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(s: &str) -> Self {
+        Answer::Text(s.to_string())
+    }
+}
+
+fn json_string(s: &str) -> String {
+    let mut result = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => result += "\\\"",
+            '\\' => result += "\\\\",
+            '\n' => result += "\\n",
+            '\t' => result += "\\t",
+            c if (c as u32) < 0x20 => result += &format!("\\u{:04x}", c as u32),
+            c => result.push(c),
+        }
+    }
+    result.push('"');
+    result
+}
+
+/// One part's answer and how long it took.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Report {
+    pub day: u32,
+    pub part: u32,
+    pub answer: Answer,
+    pub time: Duration,
+}
+
+impl Report {
+    pub fn to_json(&self) -> String {
+        format!(
+            "{{\"day\":{},\"part\":{},\"answer\":{},\"time_ms\":{:.3}}}",
+            self.day,
+            self.part,
+            self.answer.to_json(),
+            self.time.as_secs_f64() * 1000.0
+        )
+    }
+
+    /// `part1: N`, with multi-line answers starting on the next line.
+    pub fn to_human(&self) -> String {
+        match &self.answer {
+            Answer::Text(s) if s.contains('\n') => format!("part{}:\n{}", self.part, s),
+            answer => format!("part{}: {}", self.part, answer),
+        }
+    }
+}
+
+pub struct Runner {
+    day: u32,
+    json: bool,
+}
+
+impl Runner {
+    pub fn new(day: u32, json: bool) -> Self {
+        Runner { day, json }
+    }
+
+    /// A runner for the day that prints JSON if `--json` was passed.
+    pub fn from_args(day: u32) -> Self {
+        Runner::new(day, env::args().any(|arg| arg == "--json"))
+    }
+
+    /// Runs a part, timing it, and prints its answer.
+    pub fn part<A, F>(&self, part: u32, f: F) -> Report
+    where
+        A: Into<Answer>,
+        F: FnOnce() -> A,
+    {
+        let start = Instant::now();
+        let answer = f().into();
+        let report = Report {
+            day: self.day,
+            part,
+            answer,
+            time: start.elapsed(),
+        };
+        self.print(if self.json {
+            report.to_json()
+        } else {
+            report.to_human()
+        });
+        report
+    }
+
+    /// Runs work that both parts need, timing it apart from either part.
+    pub fn shared<T, F>(&self, label: &str, f: F) -> T
+    where
+        F: FnOnce() -> T,
+    {
+        let start = Instant::now();
+        let result = f();
+        let ms = start.elapsed().as_secs_f64() * 1000.0;
+        self.print(if self.json {
+            format!(
+                "{{\"day\":{},\"label\":{},\"time_ms\":{:.3}}}",
+                self.day,
+                json_string(label),
+                ms
+            )
+        } else {
+            format!("{}: {:.3} ms", label, ms)
+        });
+        result
+    }
+
+    /// Prints an intermediate result.
+    pub fn note(&self, label: &str, value: impl Into<Answer>) {
+        let value = value.into();
+        self.print(if self.json {
+            format!(
+                "{{\"day\":{},\"label\":{},\"value\":{}}}",
+                self.day,
+                json_string(label),
+                value.to_json()
+            )
+        } else {
+            format!("{}: {}", label, value)
+        });
+    }
+
+    fn print(&self, line: String) {
+        let mut stdout = io::stdout().lock();
+        writeln!(stdout, "{}", line).unwrap();
+        stdout.flush().unwrap();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_reports() {
+        let report = Report {
+            day: 3,
+            part: 2,
+            answer: 793873u64.into(),
+            time: Duration::from_micros(1500),
+        };
+        assert_eq!(report.to_human(), "part2: 793873");
+        assert_eq!(
+            report.to_json(),
+            "{\"day\":3,\"part\":2,\"answer\":793873,\"time_ms\":1.500}"
+        );
+
+        let report = Report {
+            answer: "# #\n\"a\"\\".into(),
+            ..report
+        };
+        assert_eq!(report.to_human(), "part2:\n# #\n\"a\"\\");
+        assert_eq!(
+            report.to_json(),
+            "{\"day\":3,\"part\":2,\"answer\":\"# #\\n\\\"a\\\"\\\\\",\"time_ms\":1.500}"
+        );
+
+        assert_eq!(Answer::from(-5i32), Answer::Number(-5));
+        assert_eq!(Answer::from(u64::MAX).to_string(), "18446744073709551615");
+        assert_eq!(json_string("\u{1}"), "\"\\u0001\"");
+    }
+}
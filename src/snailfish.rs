@@ -0,0 +1,277 @@
+//! Snailfish numbers stored as their regular values in order, each tagged
+//! with how many pairs it is nested inside. A full binary tree is uniquely
+//! determined by its leaf depths, so no pair nodes need to be kept around.
+
+use std::fmt;
+use std::iter::Sum;
+use std::ops::Add;
+use std::str::FromStr;
+
+const EXPLODE_DEPTH: u8 = 4;
+const SPLIT_VALUE: u32 = 10;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SNum {
+    values: Vec<(u32, u8)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Explode,
+    Split,
+}
+
+impl SNum {
+    pub fn pair(left: SNum, right: SNum) -> Self {
+        let values = left
+            .values
+            .into_iter()
+            .chain(right.values)
+            .map(|(value, depth)| (value, depth + 1))
+            .collect();
+        SNum { values }
+    }
+
+    pub fn magnitude(&self) -> u32 {
+        let mut stack: Vec<(u32, u8)> = vec![];
+        for &(value, depth) in &self.values {
+            stack.push((value, depth));
+            while stack.len() >= 2 && stack[stack.len() - 1].1 == stack[stack.len() - 2].1 {
+                let (right, depth) = stack.pop().unwrap();
+                let (left, _) = stack.pop().unwrap();
+                stack.push((3 * left + 2 * right, depth - 1));
+            }
+        }
+        stack[0].0
+    }
+
+    fn explode(&mut self) -> bool {
+        let pos = (0..self.values.len().saturating_sub(1)).find(|&i| {
+            let depth = self.values[i].1;
+            depth > EXPLODE_DEPTH && self.values[i + 1].1 == depth
+        });
+        let i = match pos {
+            Some(i) => i,
+            None => return false,
+        };
+        let (left, depth) = self.values[i];
+        let (right, _) = self.values.remove(i + 1);
+        if i > 0 {
+            self.values[i - 1].0 += left;
+        }
+        if let Some(next) = self.values.get_mut(i + 1) {
+            next.0 += right;
+        }
+        self.values[i] = (0, depth - 1);
+        true
+    }
+
+    fn split(&mut self) -> bool {
+        let pos = self
+            .values
+            .iter()
+            .position(|&(value, _)| value >= SPLIT_VALUE);
+        let i = match pos {
+            Some(i) => i,
+            None => return false,
+        };
+        let (value, depth) = self.values[i];
+        self.values[i] = (value / 2, depth + 1);
+        self.values.insert(i + 1, (value - value / 2, depth + 1));
+        true
+    }
+
+    /// Applies the next reduction action, if any is needed.
+    pub fn step(&mut self) -> Option<Action> {
+        if self.explode() {
+            Some(Action::Explode)
+        } else if self.split() {
+            Some(Action::Split)
+        } else {
+            None
+        }
+    }
+
+    pub fn reduce(&mut self) {
+        while self.step().is_some() {}
+    }
+
+    /// Reduces the number, returning each action with the number it produced.
+    pub fn reduce_trace(&mut self) -> Vec<(Action, SNum)> {
+        let mut trace = vec![];
+        while let Some(action) = self.step() {
+            trace.push((action, self.clone()));
+        }
+        trace
+    }
+
+    fn fmt_at(&self, f: &mut fmt::Formatter, pos: &mut usize, depth: u8) -> fmt::Result {
+        let (value, value_depth) = self.values[*pos];
+        if value_depth == depth {
+            *pos += 1;
+            write!(f, "{}", value)
+        } else {
+            write!(f, "[")?;
+            self.fmt_at(f, pos, depth + 1)?;
+            write!(f, ",")?;
+            self.fmt_at(f, pos, depth + 1)?;
+            write!(f, "]")
+        }
+    }
+}
+
+impl Add for SNum {
+    type Output = SNum;
+
+    fn add(self, rhs: SNum) -> SNum {
+        let mut result = SNum::pair(self, rhs);
+        result.reduce();
+        result
+    }
+}
+
+impl<'a> Add<&'a SNum> for &'a SNum {
+    type Output = SNum;
+
+    fn add(self, rhs: &'a SNum) -> SNum {
+        self.clone() + rhs.clone()
+    }
+}
+
+/// Panics on an empty iterator, as snailfish numbers have no zero.
+impl Sum for SNum {
+    fn sum<I: Iterator<Item = SNum>>(mut iter: I) -> SNum {
+        let first = iter.next().expect("can't sum no snailfish numbers");
+        iter.fold(first, |acc, num| acc + num)
+    }
+}
+
+impl fmt::Display for SNum {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.fmt_at(f, &mut 0, 0)
+    }
+}
+
+struct Parser<'a> {
+    chars: &'a [char],
+    pos: usize,
+    values: Vec<(u32, u8)>,
+}
+
+impl<'a> Parser<'a> {
+    fn expect(&mut self, c: char) -> Result<(), String> {
+        match self.chars.get(self.pos) {
+            Some(&found) if found == c => {
+                self.pos += 1;
+                Ok(())
+            }
+            Some(&found) => Err(format!(
+                "expected '{}' but found '{}' at column {}",
+                c,
+                found,
+                self.pos + 1
+            )),
+            None => Err(format!("expected '{}' at end of input", c)),
+        }
+    }
+
+    fn parse(&mut self, depth: u8) -> Result<(), String> {
+        match self.chars.get(self.pos) {
+            Some('[') => {
+                if depth == u8::MAX {
+                    return Err(format!("nesting too deep at column {}", self.pos + 1));
+                }
+                self.pos += 1;
+                self.parse(depth + 1)?;
+                self.expect(',')?;
+                self.parse(depth + 1)?;
+                self.expect(']')
+            }
+            Some(c) if c.is_ascii_digit() => {
+                let start = self.pos;
+                while self.pos < self.chars.len() && self.chars[self.pos].is_ascii_digit() {
+                    self.pos += 1;
+                }
+                let digits: String = self.chars[start..self.pos].iter().collect();
+                let value = digits
+                    .parse()
+                    .map_err(|_| format!("value too large at column {}", start + 1))?;
+                self.values.push((value, depth));
+                Ok(())
+            }
+            Some(c) => Err(format!("unexpected '{}' at column {}", c, self.pos + 1)),
+            None => Err("unexpected end of input".to_string()),
+        }
+    }
+}
+
+impl FromStr for SNum {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let chars: Vec<char> = s.trim().chars().collect();
+        let mut parser = Parser {
+            chars: &chars,
+            pos: 0,
+            values: vec![],
+        };
+        parser.parse(0)?;
+        if parser.pos != chars.len() {
+            return Err(format!("unexpected input at column {}", parser.pos + 1));
+        }
+        Ok(SNum {
+            values: parser.values,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn snum(s: &str) -> SNum {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for s in [
+            "9",
+            "[1,2]",
+            "[[1,9],[8,5]]",
+            "[[[[1,3],[5,3]],[[1,3],[8,7]]],[[[4,9],[6,9]],[[8,2],[7,3]]]]",
+        ] {
+            assert_eq!(snum(s).to_string(), s);
+        }
+        assert!("[1,2".parse::<SNum>().is_err());
+        assert!("[1;2]".parse::<SNum>().is_err());
+        assert!("[1,2]]".parse::<SNum>().is_err());
+    }
+
+    #[test]
+    fn test_reduce() {
+        let mut num = SNum::pair(snum("[[[[4,3],4],4],[7,[[8,4],9]]]"), snum("[1,1]"));
+        let trace: Vec<String> = num
+            .reduce_trace()
+            .into_iter()
+            .map(|(action, num)| format!("{:?} {}", action, num))
+            .collect();
+        assert_eq!(
+            trace,
+            [
+                "Explode [[[[0,7],4],[7,[[8,4],9]]],[1,1]]",
+                "Explode [[[[0,7],4],[15,[0,13]]],[1,1]]",
+                "Split [[[[0,7],4],[[7,8],[0,13]]],[1,1]]",
+                "Split [[[[0,7],4],[[7,8],[0,[6,7]]]],[1,1]]",
+                "Explode [[[[0,7],4],[[7,8],[6,0]]],[8,1]]",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sum() {
+        let nums = ["[1,1]", "[2,2]", "[3,3]", "[4,4]", "[5,5]", "[6,6]"];
+        let total: SNum = nums.iter().map(|s| snum(s)).sum();
+        assert_eq!(total.to_string(), "[[[[5,0],[7,4]],[5,5]],[6,6]]");
+        assert_eq!(snum("[[1,2],[[3,4],5]]").magnitude(), 143);
+    }
+}